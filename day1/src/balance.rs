@@ -0,0 +1,306 @@
+// Redistributes the food items between the elves so the heaviest bag is as light as possible.
+//
+// This is the classic "multiprocessor scheduling" problem, which is NP-hard, so:
+// - up to EXACT_SEARCH_LIMIT items we do a branch and bound search that returns the optimum
+// - above that we use the LPT heuristic (longest processing time first): sort the items from
+//   heaviest to lightest and always give the next one to the elf carrying the least. The heaviest
+//   bag it produces is never more than 4/3 of the optimal one.
+
+use std::cmp::Reverse;
+
+use crate::{Bag, Item};
+
+pub const EXACT_SEARCH_LIMIT: usize = 20;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Method {
+    Exact,
+    Heuristic,
+}
+
+#[derive(Debug)]
+pub struct Balance {
+    // The items each elf ends up with, in the order of the input bags.
+    pub bags: Vec<Vec<Item>>,
+    pub method: Method,
+}
+
+impl Balance {
+    pub fn totals(&self) -> Vec<i32> {
+        return self
+            .bags
+            .iter()
+            .map(|bag| bag.iter().map(|item| item.calories).sum())
+            .collect();
+    }
+
+    pub fn max(&self) -> i32 {
+        return self.totals().into_iter().max().unwrap_or(0);
+    }
+
+    pub fn min(&self) -> i32 {
+        return self.totals().into_iter().min().unwrap_or(0);
+    }
+
+    pub fn spread(&self) -> i32 {
+        return self.max() - self.min();
+    }
+}
+
+// Pools every item of every bag and hands them back to the same number of elves.
+pub fn balance(bags: &[Bag]) -> Balance {
+    let mut items: Vec<Item> = bags.iter().flat_map(|bag| bag.items.clone()).collect();
+    items.sort_by_key(|item| Reverse(item.calories));
+    let calories: Vec<i32> = items.iter().map(|item| item.calories).collect();
+
+    let (assignment, method) = if items.len() <= EXACT_SEARCH_LIMIT {
+        (exact(&calories, bags.len()), Method::Exact)
+    } else {
+        (lpt(&calories, bags.len()), Method::Heuristic)
+    };
+
+    let mut balanced: Vec<Vec<Item>> = vec![Vec::new(); bags.len()];
+    for (item, elf) in items.into_iter().zip(assignment) {
+        balanced[elf].push(item);
+    }
+    return Balance {
+        bags: balanced,
+        method,
+    };
+}
+
+// The elf of each item. `items` must be sorted from heaviest to lightest.
+fn lpt(items: &[i32], elves: usize) -> Vec<usize> {
+    let mut assignment: Vec<usize> = Vec::new();
+    let mut totals: Vec<i32> = vec![0; elves];
+    if elves == 0 {
+        return assignment;
+    }
+
+    for &item in items {
+        let lightest = (0..elves).min_by_key(|&i| totals[i]).unwrap();
+        assignment.push(lightest);
+        totals[lightest] += item;
+    }
+
+    return assignment;
+}
+
+struct Search<'a> {
+    items: &'a [i32],
+    lower_bound: i32,
+    loads: Vec<i32>,
+    assignment: Vec<usize>,
+    best_max: i32,
+    best_assignment: Vec<usize>,
+}
+
+impl Search<'_> {
+    fn run(&mut self, index: usize, current_max: i32) {
+        if self.best_max == self.lower_bound {
+            return;
+        }
+        if index == self.items.len() {
+            if current_max < self.best_max {
+                self.best_max = current_max;
+                self.best_assignment = self.assignment.clone();
+            }
+            return;
+        }
+
+        let item = self.items[index];
+        for elf in 0..self.loads.len() {
+            // Elves with the same load are interchangeable, trying one of them is enough.
+            if self.loads[..elf].contains(&self.loads[elf]) {
+                continue;
+            }
+            let load = self.loads[elf] + item;
+            if load >= self.best_max {
+                continue;
+            }
+
+            self.loads[elf] = load;
+            self.assignment[index] = elf;
+            self.run(index + 1, current_max.max(load));
+            self.loads[elf] -= item;
+        }
+    }
+}
+
+// The elf of each item. `items` must be sorted from heaviest to lightest, big items first prune
+// the search the most.
+fn exact(items: &[i32], elves: usize) -> Vec<usize> {
+    let start = lpt(items, elves);
+    if elves == 0 || items.is_empty() {
+        return start;
+    }
+
+    let total: i32 = items.iter().sum();
+    let elves_count = elves as i32;
+    let lower_bound = items[0].max((total + elves_count - 1) / elves_count);
+    let mut start_loads = vec![0; elves];
+    for (&item, &elf) in items.iter().zip(&start) {
+        start_loads[elf] += item;
+    }
+    let start_max = start_loads.into_iter().max().unwrap();
+
+    let mut search = Search {
+        items,
+        lower_bound,
+        loads: vec![0; elves],
+        assignment: vec![0; items.len()],
+        best_max: start_max,
+        best_assignment: Vec::new(),
+    };
+    search.run(0, 0);
+
+    if search.best_assignment.is_empty() {
+        // The heuristic was already optimal.
+        return start;
+    }
+    return search.best_assignment;
+}
+
+// day1 balance [input]
+pub fn run(args: &[String]) {
+    let path = args.first().map(|x| x.as_str()).unwrap_or("input.txt");
+    let content = std::fs::read_to_string(path).expect("correctly read input");
+    let bags = crate::exit_on_error(crate::parse_bags(&content));
+
+    let balance = balance(&bags);
+    for (i, (items, total)) in balance.bags.iter().zip(balance.totals()).enumerate() {
+        let items: Vec<String> = items.iter().map(|item| item.describe()).collect();
+        println!(
+            "{}: {} ({})",
+            bags[i].display_name(i),
+            total,
+            items.join(", ")
+        );
+    }
+    println!("method: {:?}", balance.method);
+    println!(
+        "max: {} min: {} spread: {}",
+        balance.max(),
+        balance.min(),
+        balance.spread()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xorshift::XorShift;
+
+    fn random_items(rng: &mut XorShift, count: usize) -> Vec<i32> {
+        return (0..count).map(|_| rng.below(50) as i32 + 1).collect();
+    }
+
+    // The lightest heaviest bag over every way to hand out the items.
+    fn brute_force(items: &[i32], elves: usize) -> i32 {
+        let mut best = i32::MAX;
+        let assignments = elves.pow(items.len() as u32);
+        for mut assignment in 0..assignments {
+            let mut loads = vec![0; elves];
+            for &item in items {
+                loads[assignment % elves] += item;
+                assignment /= elves;
+            }
+            best = best.min(loads.into_iter().max().unwrap());
+        }
+        return best;
+    }
+
+    fn sorted(mut items: Vec<i32>) -> Vec<i32> {
+        items.sort_by(|a, b| b.cmp(a));
+        return items;
+    }
+
+    fn bags_of(calories: &[Vec<i32>]) -> Vec<Bag> {
+        return calories
+            .iter()
+            .map(|bag| Bag {
+                name: None,
+                items: bag
+                    .iter()
+                    .map(|&calories| Item {
+                        line: 0,
+                        calories,
+                        label: None,
+                    })
+                    .collect(),
+            })
+            .collect();
+    }
+
+    fn calories_of(balance: &Balance) -> Vec<i32> {
+        return balance.bags.iter().flatten().map(|x| x.calories).collect();
+    }
+
+    #[test]
+    fn exact_matches_brute_force() {
+        let mut rng = XorShift::new(0x2545f4914f6cdd1d);
+        for count in 0..=8 {
+            for elves in 1..=3 {
+                let items = random_items(&mut rng, count);
+                // Everything starts in the first bag, the planner has to spread it.
+                let mut bags: Vec<Vec<i32>> = vec![Vec::new(); elves];
+                bags[0] = items.clone();
+
+                let balance = balance(&bags_of(&bags));
+                assert_eq!(balance.method, Method::Exact);
+                assert_eq!(balance.max(), brute_force(&items, elves), "{:?}", items);
+                assert_eq!(sorted(calories_of(&balance)), sorted(items));
+            }
+        }
+    }
+
+    #[test]
+    fn balance_keeps_every_item() {
+        let bags = crate::parse_bags("1000 a\n2000 b\n3000\n\n4000 d\n\n5000\n6000 f").unwrap();
+        let balance = balance(&bags);
+        assert_eq!(balance.bags.len(), 3);
+        let mut kept: Vec<Item> = balance.bags.concat();
+        kept.sort_by_key(|x| x.line);
+        let items: Vec<Item> = bags.iter().flat_map(|x| x.items.clone()).collect();
+        assert_eq!(kept, items);
+        assert_eq!(balance.max(), 7000);
+        assert_eq!(balance.spread(), 0);
+    }
+
+    #[test]
+    fn trailing_blank_lines_add_no_elf() {
+        let balance = balance(&crate::parse_bags("1000\n\n2000\n\n").unwrap());
+        assert_eq!(balance.bags.len(), 2);
+        assert_eq!(balance.spread(), 1000);
+    }
+
+    #[test]
+    fn lpt_stays_within_four_thirds() {
+        let mut rng = XorShift::new(0x9e3779b97f4a7c15);
+        for count in 1..=9 {
+            for elves in 1..=3 {
+                let items = sorted(random_items(&mut rng, count));
+                let mut loads = vec![0; elves];
+                for (&item, elf) in items.iter().zip(lpt(&items, elves)) {
+                    loads[elf] += item;
+                }
+                let heaviest = loads.into_iter().max().unwrap();
+                let optimum = brute_force(&items, elves);
+                assert!(
+                    3 * heaviest <= 4 * optimum,
+                    "{:?} on {} elves",
+                    items,
+                    elves
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn big_inputs_use_the_heuristic() {
+        let bags: Vec<Vec<i32>> = (1..=EXACT_SEARCH_LIMIT as i32 + 1)
+            .map(|x| vec![x])
+            .collect();
+        assert_eq!(balance(&bags_of(&bags)).method, Method::Heuristic);
+    }
+}
@@ -0,0 +1,489 @@
+// Compares two inventories, for when the elves re-pack between camps.
+//
+// Elves are matched either by their position in the file or by their name (elves without a name are
+// called "elf N" after their position, so they still match by position). Inside a matched pair
+// the items are compared as a multiset of (calories, label): moving an item around in the bag is
+// not a change.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::inventory::Inventory;
+use crate::{Bag, Item};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchBy {
+    Position,
+    Name,
+}
+
+#[derive(Debug)]
+pub struct ElfTotal {
+    pub name: String,
+    pub total: i32,
+}
+
+#[derive(Debug)]
+pub struct ElfChange {
+    pub name: String,
+    pub old_total: i32,
+    pub new_total: i32,
+    pub added: Vec<Item>,
+    pub removed: Vec<Item>,
+}
+
+impl ElfChange {
+    pub fn delta(&self) -> i32 {
+        return self.new_total - self.old_total;
+    }
+}
+
+// Ranks start at 1, `None` when the elf isn't in that inventory.
+#[derive(Debug)]
+pub struct RankMove {
+    pub name: String,
+    pub old_rank: Option<usize>,
+    pub new_rank: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct Diff {
+    pub added: Vec<ElfTotal>,
+    pub removed: Vec<ElfTotal>,
+    pub changed: Vec<ElfChange>,
+    // The new top k, followed by the elves that dropped out of the old top k.
+    pub top: Vec<RankMove>,
+}
+
+// Pairs of (old index, new index).
+fn match_elves(old: &[Bag], new: &[Bag], by: MatchBy) -> Vec<(Option<usize>, Option<usize>)> {
+    let mut pairs: Vec<(Option<usize>, Option<usize>)> = Vec::new();
+    match by {
+        MatchBy::Position => {
+            for i in 0..old.len().max(new.len()) {
+                let old_index = if i < old.len() { Some(i) } else { None };
+                let new_index = if i < new.len() { Some(i) } else { None };
+                pairs.push((old_index, new_index));
+            }
+        }
+        MatchBy::Name => {
+            // Elves sharing a name are matched in the order they appear.
+            let mut old_by_name: HashMap<String, VecDeque<usize>> = HashMap::new();
+            for (i, bag) in old.iter().enumerate() {
+                old_by_name
+                    .entry(bag.display_name(i))
+                    .or_default()
+                    .push_back(i);
+            }
+
+            let mut matched = vec![false; old.len()];
+            for (i, bag) in new.iter().enumerate() {
+                let old_index = old_by_name
+                    .get_mut(&bag.display_name(i))
+                    .and_then(|x| x.pop_front());
+                if let Some(old_index) = old_index {
+                    matched[old_index] = true;
+                }
+                pairs.push((old_index, Some(i)));
+            }
+            for (i, &is_matched) in matched.iter().enumerate() {
+                if !is_matched {
+                    pairs.push((Some(i), None));
+                }
+            }
+        }
+    }
+    return pairs;
+}
+
+// (added, removed) items between two bags.
+fn diff_items(old: &Bag, new: &Bag) -> (Vec<Item>, Vec<Item>) {
+    let mut remaining: Vec<&Item> = old.items.iter().collect();
+    let mut added: Vec<Item> = Vec::new();
+
+    for item in &new.items {
+        let same = remaining
+            .iter()
+            .position(|x| x.calories == item.calories && x.label == item.label);
+        match same {
+            Some(position) => {
+                remaining.remove(position);
+            }
+            None => added.push(item.clone()),
+        }
+    }
+
+    let removed = remaining.into_iter().cloned().collect();
+    return (added, removed);
+}
+
+fn ranking(bags: &[Bag]) -> Inventory {
+    let mut inventory = Inventory::default();
+    for bag in bags {
+        inventory.add_elf(bag.clone());
+    }
+    return inventory;
+}
+
+pub fn diff(old: &[Bag], new: &[Bag], by: MatchBy, k: usize) -> Diff {
+    let mut result = Diff {
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+        top: Vec::new(),
+    };
+
+    let pairs = match_elves(old, new, by);
+    for &pair in &pairs {
+        match pair {
+            (Some(i), None) => result.removed.push(ElfTotal {
+                name: old[i].display_name(i),
+                total: old[i].total(),
+            }),
+            (None, Some(j)) => result.added.push(ElfTotal {
+                name: new[j].display_name(j),
+                total: new[j].total(),
+            }),
+            (Some(i), Some(j)) => {
+                let (added, removed) = diff_items(&old[i], &new[j]);
+                if !added.is_empty() || !removed.is_empty() {
+                    result.changed.push(ElfChange {
+                        name: new[j].display_name(j),
+                        old_total: old[i].total(),
+                        new_total: new[j].total(),
+                        added,
+                        removed,
+                    });
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    let old_ranking = ranking(old);
+    let new_ranking = ranking(new);
+    let new_of_old: HashMap<usize, usize> = pairs
+        .iter()
+        .filter_map(|&pair| match pair {
+            (Some(i), Some(j)) => Some((i, j)),
+            _ => None,
+        })
+        .collect();
+    let old_of_new: HashMap<usize, usize> = new_of_old.iter().map(|(&i, &j)| (j, i)).collect();
+
+    for (j, _) in new_ranking.top(k) {
+        result.top.push(RankMove {
+            name: new[j].display_name(j),
+            old_rank: old_of_new.get(&j).and_then(|&i| old_ranking.rank(i)),
+            new_rank: new_ranking.rank(j),
+        });
+    }
+    for (i, _) in old_ranking.top(k) {
+        let new_rank = new_of_old.get(&i).and_then(|&j| new_ranking.rank(j));
+        if new_rank.is_none_or(|rank| rank > k) {
+            result.top.push(RankMove {
+                name: old[i].display_name(i),
+                old_rank: old_ranking.rank(i),
+                new_rank,
+            });
+        }
+    }
+
+    return result;
+}
+
+fn rank_to_string(rank: Option<usize>) -> String {
+    return rank.map_or("-".to_string(), |x| x.to_string());
+}
+
+pub fn to_text(diff: &Diff, k: usize) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for elf in &diff.removed {
+        lines.push(format!("removed: {} ({})", elf.name, elf.total));
+    }
+    for elf in &diff.added {
+        lines.push(format!("added: {} ({})", elf.name, elf.total));
+    }
+    for change in &diff.changed {
+        lines.push(format!(
+            "changed: {} {} -> {} ({:+})",
+            change.name,
+            change.old_total,
+            change.new_total,
+            change.delta()
+        ));
+        for item in &change.removed {
+            lines.push(format!("  - {} (old line {})", item.describe(), item.line));
+        }
+        for item in &change.added {
+            lines.push(format!("  + {} (new line {})", item.describe(), item.line));
+        }
+    }
+
+    lines.push(format!("top {}:", k));
+    for rank_move in &diff.top {
+        lines.push(format!(
+            "  {} -> {} {}",
+            rank_to_string(rank_move.old_rank),
+            rank_to_string(rank_move.new_rank),
+            rank_move.name
+        ));
+    }
+    return lines.join("\n");
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    return out;
+}
+
+fn json_option<T: ToString>(value: &Option<T>) -> String {
+    return value.as_ref().map_or("null".to_string(), |x| x.to_string());
+}
+
+fn json_items(items: &[Item]) -> String {
+    let items: Vec<String> = items
+        .iter()
+        .map(|item| {
+            format!(
+                "{{\"line\":{},\"calories\":{},\"label\":{}}}",
+                item.line,
+                item.calories,
+                item.label
+                    .as_deref()
+                    .map_or("null".to_string(), json_string)
+            )
+        })
+        .collect();
+    return format!("[{}]", items.join(","));
+}
+
+fn json_totals(elves: &[ElfTotal]) -> String {
+    let elves: Vec<String> = elves
+        .iter()
+        .map(|elf| {
+            format!(
+                "{{\"name\":{},\"total\":{}}}",
+                json_string(&elf.name),
+                elf.total
+            )
+        })
+        .collect();
+    return format!("[{}]", elves.join(","));
+}
+
+pub fn to_json(diff: &Diff) -> String {
+    let changed: Vec<String> = diff
+        .changed
+        .iter()
+        .map(|change| {
+            format!(
+                "{{\"name\":{},\"old_total\":{},\"new_total\":{},\"delta\":{},\"added\":{},\"removed\":{}}}",
+                json_string(&change.name),
+                change.old_total,
+                change.new_total,
+                change.delta(),
+                json_items(&change.added),
+                json_items(&change.removed)
+            )
+        })
+        .collect();
+    let top: Vec<String> = diff
+        .top
+        .iter()
+        .map(|rank_move| {
+            format!(
+                "{{\"name\":{},\"old_rank\":{},\"new_rank\":{}}}",
+                json_string(&rank_move.name),
+                json_option(&rank_move.old_rank),
+                json_option(&rank_move.new_rank)
+            )
+        })
+        .collect();
+
+    return format!(
+        "{{\"added\":{},\"removed\":{},\"changed\":[{}],\"top\":[{}]}}",
+        json_totals(&diff.added),
+        json_totals(&diff.removed),
+        changed.join(","),
+        top.join(",")
+    );
+}
+
+// day1 diff <old> <new> [--by-name] [--json] [--top k]
+pub fn run(args: &[String]) {
+    let mut paths: Vec<&str> = Vec::new();
+    let mut by = MatchBy::Position;
+    let mut json = false;
+    let mut k = 3;
+
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--by-name" => by = MatchBy::Name,
+            "--json" => json = true,
+            "--top" => {
+                k = args_iter
+                    .next()
+                    .and_then(|x| x.parse().ok())
+                    .expect("--top needs a number")
+            }
+            path => paths.push(path),
+        }
+    }
+    if paths.len() != 2 {
+        println!("usage: day1 diff <old> <new> [--by-name] [--json] [--top k]");
+        return;
+    }
+
+    let old_content = std::fs::read_to_string(paths[0]).expect("correctly read old input");
+    let new_content = std::fs::read_to_string(paths[1]).expect("correctly read new input");
+    let old = crate::exit_on_error(crate::parse_bags(&old_content));
+    let new = crate::exit_on_error(crate::parse_bags(&new_content));
+
+    let diff = diff(&old, &new, by, k);
+    if json {
+        println!("{}", to_json(&diff));
+    } else {
+        println!("{}", to_text(&diff, k));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bags(s: &str) -> Vec<Bag> {
+        return crate::parse_bags(s).unwrap();
+    }
+
+    fn moves(diff: &Diff) -> Vec<(&str, Option<usize>, Option<usize>)> {
+        return diff
+            .top
+            .iter()
+            .map(|x| (x.name.as_str(), x.old_rank, x.new_rank))
+            .collect();
+    }
+
+    fn item(line: usize, calories: i32, label: &str) -> Item {
+        return Item {
+            line,
+            calories,
+            label: Some(label.to_string()),
+        };
+    }
+
+    #[test]
+    fn duplicate_names_match_in_order() {
+        let old = bags("# A\n100\n\n# B\n200\n\n# A\n300\n");
+        let new = bags("# B\n200\n\n# A\n100\n\n# A\n300\n");
+        assert_eq!(
+            match_elves(&old, &new, MatchBy::Name),
+            vec![(Some(1), Some(0)), (Some(0), Some(1)), (Some(2), Some(2))]
+        );
+        assert!(diff(&old, &new, MatchBy::Name, 3).changed.is_empty());
+
+        // By position the first two elves swapped their bags.
+        let by_position = diff(&old, &new, MatchBy::Position, 3);
+        let changed: Vec<(&str, i32)> = by_position
+            .changed
+            .iter()
+            .map(|x| (x.name.as_str(), x.delta()))
+            .collect();
+        assert_eq!(changed, vec![("B", 100), ("A", -100)]);
+
+        // Unmatched elves on either side.
+        assert_eq!(
+            match_elves(&old, &new[..1], MatchBy::Position),
+            vec![(Some(0), Some(0)), (Some(1), None), (Some(2), None)]
+        );
+        assert_eq!(
+            match_elves(&new[..1], &old, MatchBy::Name),
+            vec![(None, Some(0)), (Some(0), Some(1)), (None, Some(2))]
+        );
+    }
+
+    #[test]
+    fn items_are_a_multiset() {
+        let old = bags("100 apple\n200 bread\n100\n100\n");
+
+        // Moving items around is not a change.
+        let moved = bags("100\n200 bread\n100\n100 apple\n");
+        assert_eq!(diff_items(&old[0], &moved[0]), (vec![], vec![]));
+
+        // A relabelled item is removed and added again, even with the same calories.
+        let relabelled = bags("100 pear\n200 bread\n100\n100\n");
+        let (added, removed) = diff_items(&old[0], &relabelled[0]);
+        assert_eq!(added, vec![item(1, 100, "pear")]);
+        assert_eq!(removed, vec![item(1, 100, "apple")]);
+
+        // Copies count.
+        let one_less = bags("100 apple\n200 bread\n100\n");
+        let (added, removed) = diff_items(&old[0], &one_less[0]);
+        assert!(added.is_empty());
+        assert_eq!(
+            removed.iter().map(|x| x.calories).collect::<Vec<_>>(),
+            vec![100]
+        );
+
+        let result = diff(&old, &relabelled, MatchBy::Position, 1);
+        assert_eq!(result.changed.len(), 1);
+        assert_eq!(result.changed[0].delta(), 0);
+    }
+
+    #[test]
+    fn top_lists_the_elves_dropping_out() {
+        let old = bags("100\n\n200\n\n300\n\n50\n");
+        let new = bags("400\n\n200\n\n10\n\n50\n");
+        let result = diff(&old, &new, MatchBy::Position, 2);
+        assert_eq!(
+            moves(&result),
+            vec![
+                ("elf 1", Some(3), Some(1)),
+                ("elf 2", Some(2), Some(2)),
+                ("elf 3", Some(1), Some(4)),
+            ]
+        );
+
+        // An elf gone from the new inventory drops out with no rank.
+        let old = bags("# X\n500\n\n# Y\n100\n");
+        let new = bags("# Y\n100\n");
+        let result = diff(&old, &new, MatchBy::Name, 1);
+        assert_eq!(
+            moves(&result),
+            vec![("Y", Some(2), Some(1)), ("X", Some(1), None)]
+        );
+        assert_eq!(
+            to_json(&result),
+            "{\"added\":[],\"removed\":[{\"name\":\"X\",\"total\":500}],\"changed\":[],\
+             \"top\":[{\"name\":\"Y\",\"old_rank\":2,\"new_rank\":1},\
+             {\"name\":\"X\",\"old_rank\":1,\"new_rank\":null}]}"
+        );
+        assert_eq!(
+            to_text(&result, 1),
+            "removed: X (500)\ntop 1:\n  2 -> 1 Y\n  1 -> - X"
+        );
+    }
+
+    #[test]
+    fn json_escapes() {
+        assert_eq!(json_string("plain"), "\"plain\"");
+        assert_eq!(
+            json_string("a \"b\" \\ c\nd\u{1}é"),
+            "\"a \\\"b\\\" \\\\ c\\u000ad\\u0001é\""
+        );
+        let items = [item(3, 100, "tab\there")];
+        assert_eq!(
+            json_items(&items),
+            "[{\"line\":3,\"calories\":100,\"label\":\"tab\\u0009here\"}]"
+        );
+    }
+}
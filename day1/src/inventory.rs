@@ -0,0 +1,590 @@
+// A mutable inventory that keeps the elves ranked by calories as it changes.
+//
+// The ranking is a treap (a binary search tree balanced with random priorities) where every node
+// knows the size of its subtree, so inserting, removing and finding the rank of an elf are all
+// O(log n), and the top k comes out of an in-order walk in O(log n + k).
+//
+// Every elf remembers the text it was loaded from until it is modified, so writing the inventory
+// back leaves the untouched elves exactly as they were.
+
+use std::cmp::Reverse;
+use std::fmt;
+
+use crate::{Bag, Item};
+
+pub type ElfId = usize;
+
+// Sorts the heaviest elf first, and on a tie the one that came first in the file.
+type Key = (Reverse<i32>, ElfId);
+type Link = Option<Box<Node>>;
+
+struct Node {
+    key: Key,
+    priority: u64,
+    size: usize,
+    left: Link,
+    right: Link,
+}
+
+fn size(link: &Link) -> usize {
+    return link.as_ref().map_or(0, |node| node.size);
+}
+
+fn update(node: &mut Node) {
+    node.size = 1 + size(&node.left) + size(&node.right);
+}
+
+// Splits into the keys smaller than `key` and the others.
+fn split(link: Link, key: &Key) -> (Link, Link) {
+    match link {
+        None => (None, None),
+        Some(mut node) => {
+            if node.key < *key {
+                let (left, right) = split(node.right.take(), key);
+                node.right = left;
+                update(&mut node);
+                (Some(node), right)
+            } else {
+                let (left, right) = split(node.left.take(), key);
+                node.left = right;
+                update(&mut node);
+                (left, Some(node))
+            }
+        }
+    }
+}
+
+// Every key of `left` must be smaller than every key of `right`.
+fn merge(left: Link, right: Link) -> Link {
+    match (left, right) {
+        (None, right) => right,
+        (left, None) => left,
+        (Some(mut left), Some(mut right)) => {
+            if left.priority > right.priority {
+                left.right = merge(left.right.take(), Some(right));
+                update(&mut left);
+                Some(left)
+            } else {
+                right.left = merge(Some(left), right.left.take());
+                update(&mut right);
+                Some(right)
+            }
+        }
+    }
+}
+
+fn remove(link: &mut Link, key: &Key) {
+    let Some(node) = link else {
+        return;
+    };
+    if node.key == *key {
+        let left = node.left.take();
+        let right = node.right.take();
+        *link = merge(left, right);
+        return;
+    }
+
+    if *key < node.key {
+        remove(&mut node.left, key);
+    } else {
+        remove(&mut node.right, key);
+    }
+    update(node);
+}
+
+fn walk(link: &Link, k: usize, out: &mut Vec<Key>) {
+    if let Some(node) = link {
+        walk(&node.left, k, out);
+        if out.len() < k {
+            out.push(node.key);
+            walk(&node.right, k, out);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Ranking {
+    root: Link,
+    seed: u64,
+}
+
+impl Ranking {
+    // splitmix64, the priorities only need to look random to keep the tree balanced.
+    fn next_priority(&mut self) -> u64 {
+        self.seed = self.seed.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        return z ^ (z >> 31);
+    }
+
+    fn insert(&mut self, key: Key) {
+        let node = Box::new(Node {
+            key,
+            priority: self.next_priority(),
+            size: 1,
+            left: None,
+            right: None,
+        });
+        let (left, right) = split(self.root.take(), &key);
+        self.root = merge(merge(left, Some(node)), right);
+    }
+
+    fn remove(&mut self, key: &Key) {
+        remove(&mut self.root, key);
+    }
+
+    // How many keys are smaller than `key`.
+    fn count_before(&self, key: &Key) -> usize {
+        let mut count = 0;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if node.key < *key {
+                count += size(&node.left) + 1;
+                link = &node.right;
+            } else {
+                link = &node.left;
+            }
+        }
+        return count;
+    }
+
+    fn first(&self, k: usize) -> Vec<Key> {
+        let mut out: Vec<Key> = Vec::new();
+        walk(&self.root, k, &mut out);
+        return out;
+    }
+}
+
+struct Elf {
+    bag: Bag,
+    total: i32,
+    // The text this elf was loaded from, dropped as soon as it changes.
+    raw: Option<String>,
+}
+
+#[derive(Default)]
+pub struct Inventory {
+    // Indexed by ElfId, removed elves leave a hole so the ids stay stable.
+    elves: Vec<Option<Elf>>,
+    ranking: Ranking,
+    // Whatever followed the last elf in the file (usually a single newline).
+    trailing: String,
+}
+
+impl Inventory {
+    pub fn parse(s: &str) -> Result<Inventory, String> {
+        let mut inventory = Inventory::default();
+        let content = s.trim_end_matches('\n');
+        if content.is_empty() {
+            inventory.trailing = s.to_string();
+            return Ok(inventory);
+        }
+        inventory.trailing = s[content.len()..].to_string();
+
+        let raw_bags = content.split("\n\n");
+        for (raw_bag, bag) in raw_bags.zip(crate::parse_bags(content)?) {
+            let id = inventory.add_elf(bag);
+            inventory.elves[id].as_mut().unwrap().raw = Some(raw_bag.to_string());
+        }
+
+        return Ok(inventory);
+    }
+
+    pub fn len(&self) -> usize {
+        return size(&self.ranking.root);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+
+    pub fn bag(&self, id: ElfId) -> Option<&Bag> {
+        return self.elf(id).map(|elf| &elf.bag);
+    }
+
+    pub fn total(&self, id: ElfId) -> Option<i32> {
+        return self.elf(id).map(|elf| elf.total);
+    }
+
+    pub fn add_elf(&mut self, bag: Bag) -> ElfId {
+        let id = self.elves.len();
+        let total = bag.total();
+        self.elves.push(Some(Elf {
+            bag,
+            total,
+            raw: None,
+        }));
+        self.ranking.insert((Reverse(total), id));
+        return id;
+    }
+
+    pub fn remove_elf(&mut self, id: ElfId) -> Option<Bag> {
+        let elf = self.elves.get_mut(id)?.take()?;
+        self.ranking.remove(&(Reverse(elf.total), id));
+        return Some(elf.bag);
+    }
+
+    pub fn add_item(&mut self, id: ElfId, calories: i32, label: Option<String>) -> bool {
+        let item = Item {
+            line: 0,
+            calories,
+            label,
+        };
+        return self
+            .change_items(id, |items| {
+                items.push(item);
+                Some(())
+            })
+            .is_some();
+    }
+
+    // Removes the item at `index` in the elf's bag.
+    pub fn remove_item(&mut self, id: ElfId, index: usize) -> Option<Item> {
+        return self.change_items(id, |items| {
+            if index < items.len() {
+                Some(items.remove(index))
+            } else {
+                None
+            }
+        });
+    }
+
+    // The k heaviest elves with their totals, heaviest first.
+    pub fn top(&self, k: usize) -> Vec<(ElfId, i32)> {
+        return self
+            .ranking
+            .first(k)
+            .into_iter()
+            .map(|(Reverse(total), id)| (id, total))
+            .collect();
+    }
+
+    // 1 for the heaviest elf.
+    pub fn rank(&self, id: ElfId) -> Option<usize> {
+        let total = self.total(id)?;
+        return Some(self.ranking.count_before(&(Reverse(total), id)) + 1);
+    }
+
+    fn elf(&self, id: ElfId) -> Option<&Elf> {
+        return self.elves.get(id).and_then(|elf| elf.as_ref());
+    }
+
+    fn change_items<T>(
+        &mut self,
+        id: ElfId,
+        change: impl FnOnce(&mut Vec<Item>) -> Option<T>,
+    ) -> Option<T> {
+        let elf = self.elves.get_mut(id)?.as_mut()?;
+        let old_key = (Reverse(elf.total), id);
+        let result = change(&mut elf.bag.items)?;
+
+        elf.total = elf.bag.total();
+        elf.raw = None;
+        let new_key = (Reverse(elf.total), id);
+        self.ranking.remove(&old_key);
+        self.ranking.insert(new_key);
+        return Some(result);
+    }
+}
+
+impl fmt::Display for Inventory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        for elf in self.elves.iter().flatten() {
+            if !first {
+                write!(f, "\n\n")?;
+            }
+            first = false;
+
+            match &elf.raw {
+                Some(raw) => write!(f, "{}", raw)?,
+                None => write!(f, "{}", crate::named::format_bag(&elf.bag))?,
+            }
+        }
+        return write!(f, "{}", self.trailing);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Edit {
+    AddElf(Vec<i32>),
+    RemoveElf(ElfId),
+    AddItem(ElfId, i32, Option<String>),
+    // The item is numbered from 1 in the bag.
+    RemoveItem(ElfId, usize),
+}
+
+// Elves are numbered from 1 on the command line, in the order they were added.
+fn parse_elf(s: &str) -> Result<ElfId, String> {
+    return match s.parse::<usize>() {
+        Ok(number) if number > 0 => Ok(number - 1),
+        _ => Err(format!("{:?} is not an elf number", s)),
+    };
+}
+
+fn parse_calories(s: &str) -> Result<i32, String> {
+    return s
+        .parse::<i32>()
+        .map_err(|_| format!("{:?} is not a calorie count", s));
+}
+
+// add-elf:1000,2000 remove-elf:3 add-item:3:500[:label] remove-item:3:1
+fn parse_edit(s: &str) -> Result<Edit, String> {
+    let parts: Vec<&str> = s.splitn(4, ':').collect();
+    return match parts[..] {
+        ["add-elf", items] => {
+            let calories: Result<Vec<i32>, String> = items
+                .split(',')
+                .filter(|x| !x.is_empty())
+                .map(parse_calories)
+                .collect();
+            Ok(Edit::AddElf(calories?))
+        }
+        ["remove-elf", elf] => Ok(Edit::RemoveElf(parse_elf(elf)?)),
+        ["add-item", elf, calories] => Ok(Edit::AddItem(
+            parse_elf(elf)?,
+            parse_calories(calories)?,
+            None,
+        )),
+        ["add-item", elf, calories, label] => Ok(Edit::AddItem(
+            parse_elf(elf)?,
+            parse_calories(calories)?,
+            Some(label.to_string()),
+        )),
+        ["remove-item", elf, item] => match item.parse::<usize>() {
+            Ok(number) if number > 0 => Ok(Edit::RemoveItem(parse_elf(elf)?, number - 1)),
+            _ => Err(format!("{:?} is not an item number", item)),
+        },
+        _ => Err(format!("unknown edit {:?}", s)),
+    };
+}
+
+// Applies the edit and returns the elf it touched, if it is still there.
+fn apply(inventory: &mut Inventory, edit: Edit) -> Result<Option<ElfId>, String> {
+    let missing = |id: ElfId| format!("there is no elf {}", id + 1);
+    return match edit {
+        Edit::AddElf(calories) => {
+            let items = calories
+                .into_iter()
+                .map(|calories| Item {
+                    line: 0,
+                    calories,
+                    label: None,
+                })
+                .collect();
+            Ok(Some(inventory.add_elf(Bag { name: None, items })))
+        }
+        Edit::RemoveElf(id) => match inventory.remove_elf(id) {
+            Some(_) => Ok(None),
+            None => Err(missing(id)),
+        },
+        Edit::AddItem(id, calories, label) => {
+            if inventory.add_item(id, calories, label) {
+                Ok(Some(id))
+            } else {
+                Err(missing(id))
+            }
+        }
+        Edit::RemoveItem(id, _) if inventory.bag(id).is_none() => Err(missing(id)),
+        Edit::RemoveItem(id, index) => match inventory.remove_item(id, index) {
+            Some(_) => Ok(Some(id)),
+            None => Err(format!("elf {} has no item {}", id + 1, index + 1)),
+        },
+    };
+}
+
+// day1 edit <input> <output> <edit>...
+// Applies the edits in order, printing where the touched elf ranks after each one, and writes the
+// result to <output>. The elves that weren't touched are written back exactly as they were read.
+pub fn run_edit(args: &[String]) {
+    if args.len() < 2 {
+        println!("usage: day1 edit <input> <output> <edit>...");
+        println!(
+            "edits: add-elf:1000,2000 remove-elf:N add-item:N:calories[:label] remove-item:N:M"
+        );
+        return;
+    }
+    let content = std::fs::read_to_string(&args[0]).expect("correctly read input");
+    let mut inventory = crate::exit_on_error(Inventory::parse(&content));
+
+    for arg in &args[2..] {
+        let touched = parse_edit(arg).and_then(|edit| apply(&mut inventory, edit));
+        match touched {
+            Ok(Some(id)) => println!(
+                "{}: {} now ranks {} of {} with {}",
+                arg,
+                inventory.bag(id).unwrap().display_name(id),
+                inventory.rank(id).unwrap(),
+                inventory.len(),
+                inventory.total(id).unwrap()
+            ),
+            Ok(None) => println!("{}: {} elves left", arg, inventory.len()),
+            Err(message) => {
+                println!("{}: {}", arg, message);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    std::fs::write(&args[1], inventory.to_string()).expect("correctly write output");
+    if inventory.is_empty() {
+        println!("no elves left");
+        return;
+    }
+    let (id, total) = inventory.top(1)[0];
+    println!(
+        "heaviest: {} with {}",
+        inventory.bag(id).unwrap().display_name(id),
+        total
+    );
+}
+
+// day1 top [k] [input]
+pub fn run(args: &[String]) {
+    let k: usize = args
+        .first()
+        .map_or(3, |x| x.parse().expect("k must be a number"));
+    let path = args.get(1).map(|x| x.as_str()).unwrap_or("input.txt");
+    let content = std::fs::read_to_string(path).expect("correctly read input");
+    let inventory = crate::exit_on_error(Inventory::parse(&content));
+
+    let top = inventory.top(k);
+    for (rank, (id, total)) in top.iter().enumerate() {
+        let name = inventory.bag(*id).unwrap().display_name(*id);
+        println!("{}. {}: {}", rank + 1, name, total);
+    }
+    let sum: i32 = top.iter().map(|(_, total)| total).sum();
+    println!("top {} total: {}", k, sum);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xorshift::XorShift;
+
+    // The elves by id as plain calorie lists, None once removed.
+    type Model = Vec<Option<Vec<i32>>>;
+
+    fn model_top(model: &Model) -> Vec<(ElfId, i32)> {
+        let mut totals: Vec<(ElfId, i32)> = model
+            .iter()
+            .enumerate()
+            .filter_map(|(id, bag)| bag.as_ref().map(|x| (id, x.iter().sum())))
+            .collect();
+        totals.sort_by_key(|&(id, total)| (Reverse(total), id));
+        return totals;
+    }
+
+    fn check(inventory: &Inventory, model: &Model) {
+        let expected = model_top(model);
+        assert_eq!(inventory.len(), expected.len());
+        assert_eq!(inventory.is_empty(), expected.is_empty());
+        for k in [0, 1, 3, expected.len() + 1] {
+            let top: Vec<(ElfId, i32)> = expected.iter().copied().take(k).collect();
+            assert_eq!(inventory.top(k), top);
+        }
+        for (rank, &(id, total)) in expected.iter().enumerate() {
+            assert_eq!(inventory.rank(id), Some(rank + 1));
+            assert_eq!(inventory.total(id), Some(total));
+        }
+        for (id, bag) in model.iter().enumerate() {
+            if bag.is_none() {
+                assert_eq!(inventory.rank(id), None);
+            }
+        }
+    }
+
+    fn calories_of(bag: &Bag) -> Vec<i32> {
+        return bag.items.iter().map(|x| x.calories).collect();
+    }
+
+    #[test]
+    fn edits_keep_the_ranking() {
+        let mut inventory = Inventory::parse("1000\n2000\n3000\n\n4000\n\n5000\n6000\n").unwrap();
+        let mut model: Model = vec![
+            Some(vec![1000, 2000, 3000]),
+            Some(vec![4000]),
+            Some(vec![5000, 6000]),
+        ];
+        check(&inventory, &model);
+
+        let mut rng = XorShift::new(0x2545f4914f6cdd1d);
+        for _ in 0..2000 {
+            let seed = rng.next_u64();
+            let id = (seed >> 8) as usize % (model.len() + 1);
+            let calories = (seed >> 32) as i32 % 50 * 100;
+            match seed % 4 {
+                0 => {
+                    let bag = Bag {
+                        name: None,
+                        items: vec![Item {
+                            line: 0,
+                            calories,
+                            label: None,
+                        }],
+                    };
+                    assert_eq!(inventory.add_elf(bag), model.len());
+                    model.push(Some(vec![calories]));
+                }
+                1 => {
+                    let removed = inventory.remove_elf(id);
+                    let expected = model.get_mut(id).and_then(|x| x.take());
+                    assert_eq!(removed.as_ref().map(calories_of), expected);
+                }
+                2 => {
+                    let added = inventory.add_item(id, calories, Some("snack".to_string()));
+                    let bag = model.get_mut(id).and_then(|x| x.as_mut());
+                    assert_eq!(added, bag.is_some());
+                    if let Some(bag) = bag {
+                        bag.push(calories);
+                    }
+                }
+                _ => {
+                    let index = (seed >> 16) as usize % 3;
+                    let removed = inventory.remove_item(id, index);
+                    let bag = model.get_mut(id).and_then(|x| x.as_mut());
+                    let expected = bag.filter(|x| index < x.len()).map(|x| x.remove(index));
+                    assert_eq!(removed.map(|x| x.calories), expected);
+                }
+            }
+            check(&inventory, &model);
+        }
+    }
+
+    #[test]
+    fn ties_rank_the_first_elf_first() {
+        let inventory = Inventory::parse("3000\n\n1000\n2000\n\n3000\n").unwrap();
+        assert_eq!(inventory.top(3), vec![(0, 3000), (1, 3000), (2, 3000)]);
+        assert_eq!(inventory.rank(2), Some(3));
+    }
+
+    #[test]
+    fn round_trip() {
+        for input in [
+            "",
+            "\n",
+            "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000",
+            "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n",
+            "# Alice\n1000 dried mango\n2000\n\n# Bob\n4000   trail mix  \n\n\n",
+        ] {
+            assert_eq!(Inventory::parse(input).unwrap().to_string(), input);
+        }
+    }
+
+    #[test]
+    fn untouched_elves_are_written_back_as_read() {
+        let input = "# Alice\n1000   dried mango\n2000\n\n4000\n\n# Carol\n5000 tea  \n6000\n";
+        let mut inventory = Inventory::parse(input).unwrap();
+
+        assert!(inventory.add_item(1, 500, Some("nuts".to_string())));
+        assert_eq!(
+            inventory.to_string(),
+            "# Alice\n1000   dried mango\n2000\n\n4000\n500 nuts\n\n# Carol\n5000 tea  \n6000\n"
+        );
+
+        assert_eq!(inventory.remove_item(2, 0).map(|x| x.calories), Some(5000));
+        assert!(inventory.remove_elf(0).is_some());
+        assert_eq!(inventory.to_string(), "4000\n500 nuts\n\n# Carol\n6000\n");
+    }
+}
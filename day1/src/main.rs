@@ -1,4 +1,4 @@
-#![allow(unused)]
+#![allow(clippy::needless_return)]
 /*
  * --- Day 1: Calorie Counting ---
 Santa's reindeer typically eat regular reindeer food, but they need a lot of magical energy to deliver presents on Christmas. For that, their favorite snack is a special type of star fruit that only grows deep in the jungle. The Elves have brought you on their annual expedition to the grove where the fruit grows.
@@ -62,54 +62,128 @@ Find the Elf carrying the most Calories. How many total Calories is that Elf car
 //     println!("{:?}", wbyl)
 // }
 
+mod balance;
+mod diff;
+mod inventory;
+mod named;
+mod parallel;
+mod subset;
+#[cfg(test)]
+mod xorshift;
+
 use std::env;
 use std::fs;
-use std::fs::File;
-use std::io::{self, Read};
-
-fn file_to_string(s: &str) -> io::Result<String> {
-    let mut file = File::open(s)?;
-    let mut s = String::new();
-    file.read_to_string(&mut s);
-    Ok(s)
+
+// One food item, `line` is where it was written in the input (starting at 1, 0 if it was added
+// afterwards).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    pub line: usize,
+    pub calories: i32,
+    pub label: Option<String>,
 }
 
-fn sum_bags(s: &str) -> Vec<i32> {
-    let elf_bags: Vec<&str> = s.split("\n\n").collect();
-    let mut bags: Vec<i32> = Vec::new();
+impl Item {
+    pub fn describe(&self) -> String {
+        return match &self.label {
+            Some(label) => format!("{} {}", self.calories, label),
+            None => self.calories.to_string(),
+        };
+    }
+}
+
+// Everything one elf carries. The name comes from an optional `# Name` header line.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Bag {
+    pub name: Option<String>,
+    pub items: Vec<Item>,
+}
+
+impl Bag {
+    pub fn total(&self) -> i32 {
+        return self.items.iter().map(|item| item.calories).sum();
+    }
+
+    // `index` is the position of the bag in the inventory, used when the elf has no name.
+    pub fn display_name(&self, index: usize) -> String {
+        return match &self.name {
+            Some(name) => name.clone(),
+            None => format!("elf {}", index + 1),
+        };
+    }
+}
+
+// Parses one line of a bag, either `1000` or `1000 dried mango`.
+fn parse_item(line: usize, s: &str) -> Result<Item, String> {
+    let (calories, label) = match s.split_once(' ') {
+        Some((calories, label)) => (calories, Some(label.trim().to_string())),
+        None => (s, None),
+    };
+    let calories = calories
+        .parse::<i32>()
+        .map_err(|_| format!("line {}: {:?} is not a calorie count", line, s))?;
+
+    return Ok(Item {
+        line,
+        calories,
+        label: label.filter(|x| !x.is_empty()),
+    });
+}
+
+// Blank lines at the end of the input don't make elves, an input without any line has none.
+fn parse_bags(s: &str) -> Result<Vec<Bag>, String> {
+    let content = s.trim_end_matches('\n');
+    if content.is_empty() {
+        return Ok(Vec::new());
+    }
+    let elf_bags: Vec<&str> = content.split("\n\n").collect();
+    let mut bags: Vec<Bag> = Vec::new();
+    let mut first_line = 1;
 
     for raw_bag in elf_bags {
-        let total_for_bag: i32 = raw_bag.lines().map(|x| x.parse::<i32>().unwrap()).sum();
-        bags.push(total_for_bag);
+        let mut bag = Bag::default();
+        for (i, x) in raw_bag.lines().enumerate() {
+            match x.strip_prefix('#') {
+                Some(name) if i == 0 => bag.name = Some(name.trim().to_string()),
+                Some(_) => {
+                    return Err(format!(
+                        "line {}: a `# Name` header must be the first line of its elf",
+                        first_line + i
+                    ));
+                }
+                None => bag.items.push(parse_item(first_line + i, x)?),
+            }
+        }
+        bags.push(bag);
+        // The lines of this bag plus the blank separator.
+        first_line += raw_bag.matches('\n').count() + 2;
     }
 
-    return bags;
+    return Ok(bags);
 }
 
-fn part1() {
-    let content = fs::read_to_string("input.txt").expect("correctly read input");
+fn sum_bags(s: &str) -> Result<Vec<i32>, String> {
+    let mut bags: Vec<i32> = Vec::new();
 
-    // let content2 = file_to_string("input.txt").expect("Coulnd't read the input.txt");
-    let mut all_sums = sum_bags(&content);
-    all_sums.sort();
-    // let elf_bags: Vec<&str> = content.split("\n\n").collect();
-
-    // let mut max_so_far = 0;
-    // for bag in elf_bags {
-    //     let total_for_bag: i32 = bag.lines().map(|x| x.parse::<i32>().unwrap()).sum();
-    //     max_so_far = if total_for_bag > max_so_far {
-    //         total_for_bag
-    //     } else {
-    //         max_so_far
-    //     };
-    // }
+    for bag in parse_bags(s)? {
+        let total_for_bag: i32 = bag.total();
+        bags.push(total_for_bag);
+    }
 
-    println!("answer: {}", all_sums.last().unwrap())
+    return Ok(bags);
+}
+
+// For the modes: prints why the input couldn't be read and stops.
+fn exit_on_error<T>(result: Result<T, String>) -> T {
+    return result.unwrap_or_else(|message| {
+        println!("{}", message);
+        std::process::exit(1);
+    });
 }
 
 fn part2() {
     let content = fs::read_to_string("input.txt").expect("correctly read input");
-    let mut all_sums = sum_bags(&content);
+    let mut all_sums = exit_on_error(sum_bags(&content));
     all_sums.sort_by(|a,b| b.cmp(a));
 
     let sum_of_3: i32 = all_sums[0..3].iter().sum();
@@ -119,7 +193,17 @@ fn part2() {
 }
 
 fn main() {
-    part2();
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(|x| x.as_str()) {
+        Some("balance") => balance::run(&args[2..]),
+        Some("convert") => named::run(&args[2..]),
+        Some("diff") => diff::run(&args[2..]),
+        Some("edit") => inventory::run_edit(&args[2..]),
+        Some("parallel") => parallel::run(&args[2..]),
+        Some("subset") => subset::run(&args[2..]),
+        Some("top") => inventory::run(&args[2..]),
+        _ => part2(),
+    }
     // let mut acc = 0;
     // for l in content2.unwrap().lines(){
     //     ifl.parse<i32>(){
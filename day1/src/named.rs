@@ -0,0 +1,118 @@
+// The named inventory format, a superset of the plain one:
+//
+// # Alice
+// 1000 dried mango
+// 2000
+//
+// # Bob
+// 4000 trail mix
+//
+// Both the `# Name` header and the item labels are optional, so a plain inventory is also a valid
+// named one and `parse_bags` reads both.
+
+use crate::Bag;
+
+pub fn format_bag(bag: &Bag) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    if let Some(name) = &bag.name {
+        lines.push(format!("# {}", name));
+    }
+    for item in &bag.items {
+        lines.push(item.describe());
+    }
+    return lines.join("\n");
+}
+
+// Every elf gets a header, the ones without a name are called after their position, the way
+// `Bag::display_name` calls them, so they still match the plain file by name.
+pub fn to_named(bags: &[Bag]) -> String {
+    let mut blocks: Vec<String> = Vec::new();
+    for (i, bag) in bags.iter().enumerate() {
+        let mut named = bag.clone();
+        named.name = Some(bag.display_name(i));
+        blocks.push(format_bag(&named));
+    }
+    return blocks.join("\n\n") + "\n";
+}
+
+// Drops the names and the labels.
+pub fn to_plain(bags: &[Bag]) -> String {
+    let mut blocks: Vec<String> = Vec::new();
+    for bag in bags {
+        let lines: Vec<String> = bag.items.iter().map(|x| x.calories.to_string()).collect();
+        blocks.push(lines.join("\n"));
+    }
+    return blocks.join("\n\n") + "\n";
+}
+
+// day1 convert <plain|named> [input]
+pub fn run(args: &[String]) {
+    let format = args.first().map(|x| x.as_str());
+    let path = args.get(1).map(|x| x.as_str()).unwrap_or("input.txt");
+    let content = std::fs::read_to_string(path).expect("correctly read input");
+    let bags = crate::exit_on_error(crate::parse_bags(&content));
+
+    match format {
+        Some("plain") => print!("{}", to_plain(&bags)),
+        Some("named") => print!("{}", to_named(&bags)),
+        _ => println!("usage: day1 convert <plain|named> [input]"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::{diff, MatchBy};
+
+    const NAMED: &str = "# Alice\n1000 dried mango\n2000\n\n4000 trail mix\n\n# Carol\n5000\n";
+
+    #[test]
+    fn converted_elves_keep_their_names() {
+        let bags = crate::parse_bags(NAMED).unwrap();
+        let named = to_named(&bags);
+        assert_eq!(
+            named,
+            "# Alice\n1000 dried mango\n2000\n\n# elf 2\n4000 trail mix\n\n# Carol\n5000\n"
+        );
+
+        let converted = crate::parse_bags(&named).unwrap();
+        let changes = diff(&bags, &converted, MatchBy::Name, 3);
+        assert!(changes.added.is_empty() && changes.removed.is_empty());
+        assert!(changes.changed.is_empty());
+    }
+
+    #[test]
+    fn plain_drops_names_and_labels() {
+        let bags = crate::parse_bags(NAMED).unwrap();
+        assert_eq!(to_plain(&bags), "1000\n2000\n\n4000\n\n5000\n");
+    }
+
+    #[test]
+    fn trailing_blank_lines_make_no_elf() {
+        for input in [
+            "1000\n\n2000",
+            "1000\n\n2000\n",
+            "1000\n\n2000\n\n",
+            "1000\n\n2000\n\n\n",
+        ] {
+            let totals: Vec<i32> = crate::parse_bags(input)
+                .unwrap()
+                .iter()
+                .map(|x| x.total())
+                .collect();
+            assert_eq!(totals, vec![1000, 2000], "{:?}", input);
+        }
+        assert!(crate::parse_bags("").unwrap().is_empty());
+        assert!(crate::parse_bags("\n\n").unwrap().is_empty());
+        // Blank lines anywhere else still separate empty elves.
+        assert_eq!(crate::parse_bags("\n\n1000\n").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn misplaced_header_is_an_error() {
+        let error = crate::parse_bags("1000\n# Bob\n2000").unwrap_err();
+        assert!(error.starts_with("line 2:"), "{}", error);
+        let error = crate::parse_bags("1000\n\n2000\nmango").unwrap_err();
+        assert!(error.starts_with("line 4:"), "{}", error);
+    }
+}
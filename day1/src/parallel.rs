@@ -0,0 +1,199 @@
+// Sums the bags of very large inventories on several threads.
+//
+// The file is never loaded as a whole: it is cut into chunks of about `chunk_size` bytes, each cut
+// made on a blank line, and the worker threads take the chunks one by one, read them from their
+// own file handle and run `sum_bags` on them. The totals are put back in chunk order, so the result
+// is the same as `sum_bags` on the whole file.
+//
+// `split("\n\n")` cuts a run of newlines from its start, two at a time, so the chunks are only cut
+// at the start of such a run: the next chunk then starts exactly where the sequential split would
+// continue.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+pub const DEFAULT_CHUNK_SIZE: u64 = 64 * 1024 * 1024;
+
+// Byte range of a chunk, without the blank line separating it from the next one.
+#[derive(Debug, Clone, Copy)]
+struct Chunk {
+    start: u64,
+    end: u64,
+}
+
+// The totals of every chunk a worker went through, with the chunk index.
+type WorkerSums = io::Result<Vec<(usize, Vec<i32>)>>;
+
+// First position at or after `offset` where a run of at least two newlines starts.
+fn find_cut(file: &mut File, offset: u64) -> io::Result<Option<u64>> {
+    // Read from the byte before `offset` to know if it is already inside a run of newlines.
+    let mut position = offset.saturating_sub(1);
+    file.seek(SeekFrom::Start(position))?;
+
+    let mut previous: Option<u8> = None;
+    let mut run_start: Option<u64> = None;
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(None);
+        }
+
+        for &byte in &buffer[..read] {
+            if byte == b'\n' && previous != Some(b'\n') {
+                run_start = Some(position);
+            }
+            if byte == b'\n' && previous == Some(b'\n') {
+                if let Some(start) = run_start.filter(|&start| start >= offset) {
+                    return Ok(Some(start));
+                }
+            }
+            previous = Some(byte);
+            position += 1;
+        }
+    }
+}
+
+fn cut_chunks(path: &str, chunk_size: u64) -> io::Result<Vec<Chunk>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut chunks: Vec<Chunk> = Vec::new();
+
+    let mut start = 0;
+    while start + chunk_size < len {
+        match find_cut(&mut file, start + chunk_size)? {
+            Some(cut) => {
+                chunks.push(Chunk { start, end: cut });
+                start = cut + 2;
+            }
+            None => break,
+        }
+    }
+    chunks.push(Chunk { start, end: len });
+
+    return Ok(chunks);
+}
+
+fn sum_chunk(file: &mut File, chunk: Chunk) -> io::Result<Vec<i32>> {
+    file.seek(SeekFrom::Start(chunk.start))?;
+    let mut content = String::new();
+    file.take(chunk.end - chunk.start)
+        .read_to_string(&mut content)?;
+    return crate::sum_bags(&content).map_err(|message| {
+        let message = format!("in the chunk at byte {}, {}", chunk.start, message);
+        io::Error::new(io::ErrorKind::InvalidData, message)
+    });
+}
+
+pub fn sum_bags_parallel(path: &str, threads: usize, chunk_size: u64) -> io::Result<Vec<i32>> {
+    let chunks = cut_chunks(path, chunk_size.max(1))?;
+    let next_chunk = AtomicUsize::new(0);
+
+    let results: Vec<WorkerSums> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut file = File::open(path)?;
+                    let mut done: Vec<(usize, Vec<i32>)> = Vec::new();
+                    loop {
+                        let index = next_chunk.fetch_add(1, Ordering::Relaxed);
+                        if index >= chunks.len() {
+                            return Ok(done);
+                        }
+                        done.push((index, sum_chunk(&mut file, chunks[index])?));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .collect()
+    });
+
+    let mut by_chunk: Vec<Vec<i32>> = vec![Vec::new(); chunks.len()];
+    for result in results {
+        for (index, sums) in result? {
+            by_chunk[index] = sums;
+        }
+    }
+    return Ok(by_chunk.concat());
+}
+
+fn default_threads() -> usize {
+    return thread::available_parallelism().map_or(1, |x| x.get());
+}
+
+// day1 parallel [input]
+pub fn run(args: &[String]) {
+    let path = args.first().map(|x| x.as_str()).unwrap_or("input.txt");
+    let mut all_sums = sum_bags_parallel(path, default_threads(), DEFAULT_CHUNK_SIZE)
+        .expect("correctly read input");
+    all_sums.sort_by(|a, b| b.cmp(a));
+
+    println!("answer: {}", all_sums[0]);
+    let sum_of_3: i32 = all_sums.iter().take(3).sum();
+    println!("answer part2 {}", sum_of_3);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Writes `content` to a file of its own and compares both paths on it, for every chunk size
+    // up to the whole file so each cut position gets tried.
+    fn check(name: &str, content: &str) {
+        let path =
+            std::env::temp_dir().join(format!("day1-parallel-{}-{}", std::process::id(), name));
+        std::fs::write(&path, content).unwrap();
+        let path_str = path.to_str().unwrap();
+
+        let expected = crate::sum_bags(content).ok();
+        for chunk_size in 1..=content.len() as u64 + 1 {
+            for threads in [1, 2, 4] {
+                let sums = sum_bags_parallel(path_str, threads, chunk_size).ok();
+                assert_eq!(
+                    sums, expected,
+                    "{} with chunks of {} on {} threads",
+                    name, chunk_size, threads
+                );
+            }
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn example() {
+        check(
+            "example",
+            "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n",
+        );
+    }
+
+    #[test]
+    fn runs_of_blank_lines() {
+        check("runs", "1\n\n\n\n22\n\n\n333\n\n\n\n\n4444\n");
+        check("pairs", "1\n\n\n\n\n\n2\n");
+        // A single extra newline ends up inside the next bag, which both paths must reject.
+        check("odd", "1\n\n\n2\n");
+    }
+
+    #[test]
+    fn leading_and_trailing_blanks() {
+        check("leading", "\n\n1000\n\n2000\n");
+        check("trailing", "1000\n\n2000\n\n");
+        check("both", "\n\n\n\n1000\n2000\n\n\n\n");
+        check("no newline", "1000\n\n2000");
+        check("empty", "");
+    }
+
+    #[test]
+    fn single_huge_bag() {
+        let bag: Vec<String> = (1..=200).map(|x| x.to_string()).collect();
+        let bag = bag.join("\n");
+        check("huge", &format!("{}\n", bag));
+        check("huge between", &format!("7\n\n{}\n\n8\n", bag));
+    }
+}
@@ -0,0 +1,312 @@
+// Picks food items whose calories add up to a target, or as close to it as possible.
+//
+// The closest sum above the target can't be more than `target + biggest item` (otherwise we could
+// drop any item and still be above the target), so the search only needs sums up to that bound:
+// - when the bound is small enough we run a subset sum dynamic programming over every sum
+// - otherwise, with few enough items, we meet in the middle: enumerate every subset sum of each
+//   half and binary search the best partner in the other half
+// - otherwise we give up and ask to restrict the search (for example to one elf's bag)
+
+use crate::{Bag, Item};
+
+pub const DP_SUM_LIMIT: i64 = 5_000_000;
+pub const MEET_IN_THE_MIDDLE_LIMIT: usize = 40;
+
+// A chosen item, `elf` is the index of its bag in the inventory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Choice {
+    pub elf: usize,
+    pub item: Item,
+}
+
+#[derive(Debug)]
+pub struct Selection {
+    pub target: i64,
+    pub total: i64,
+    pub choices: Vec<Choice>,
+}
+
+impl Selection {
+    pub fn is_exact(&self) -> bool {
+        return self.total == self.target;
+    }
+}
+
+// Searches every bag, or only the bag at index `elf` when one is given.
+pub fn closest_subset(bags: &[Bag], target: i64, elf: Option<usize>) -> Result<Selection, String> {
+    if let Some(elf) = elf {
+        if elf >= bags.len() {
+            return Err(format!("there is no elf {}", elf + 1));
+        }
+    }
+
+    let mut candidates: Vec<Choice> = Vec::new();
+    for (index, bag) in bags.iter().enumerate() {
+        if elf.is_some() && elf != Some(index) {
+            continue;
+        }
+        for item in &bag.items {
+            candidates.push(Choice {
+                elf: index,
+                item: item.clone(),
+            });
+        }
+    }
+
+    let biggest = candidates
+        .iter()
+        .map(|choice| choice.item.calories as i64)
+        .max()
+        .unwrap_or(0);
+    let bound = target.max(0) + biggest;
+
+    let picked = if bound <= DP_SUM_LIMIT {
+        dynamic_programming(&candidates, target, bound as usize)
+    } else if candidates.len() <= MEET_IN_THE_MIDDLE_LIMIT {
+        meet_in_the_middle(&candidates, target)
+    } else {
+        return Err(format!(
+            "{} items with a target of {} is too big to search, pick a single elf",
+            candidates.len(),
+            target
+        ));
+    };
+
+    let choices: Vec<Choice> = picked.into_iter().map(|i| candidates[i].clone()).collect();
+    let total = choices
+        .iter()
+        .map(|choice| choice.item.calories as i64)
+        .sum();
+    return Ok(Selection {
+        target,
+        total,
+        choices,
+    });
+}
+
+// Is `sum` a better answer than `best` for `target`? On a tie we'd rather stay under budget.
+fn is_closer(sum: i64, best: i64, target: i64) -> bool {
+    let distance = (sum - target).abs();
+    let best_distance = (best - target).abs();
+    return distance < best_distance || (distance == best_distance && sum < best);
+}
+
+fn dynamic_programming(candidates: &[Choice], target: i64, bound: usize) -> Vec<usize> {
+    // reached_by[sum] is 1 + the index of the item that first reached `sum`, 0 if not reached.
+    // Items are added in order, so following the chain back only ever visits smaller indices.
+    let mut reached_by: Vec<u32> = vec![0; bound + 1];
+    let mut reached: Vec<bool> = vec![false; bound + 1];
+    reached[0] = true;
+
+    for (index, choice) in candidates.iter().enumerate() {
+        let calories = choice.item.calories as usize;
+        if calories == 0 || calories > bound {
+            continue;
+        }
+        for sum in (calories..=bound).rev() {
+            if reached[sum - calories] && !reached[sum] {
+                reached[sum] = true;
+                reached_by[sum] = index as u32 + 1;
+            }
+        }
+    }
+
+    let mut best = 0;
+    for (sum, &is_reached) in reached.iter().enumerate() {
+        if is_reached && is_closer(sum as i64, best as i64, target) {
+            best = sum;
+        }
+    }
+
+    let mut picked: Vec<usize> = Vec::new();
+    let mut sum = best;
+    while sum > 0 {
+        let index = reached_by[sum] as usize - 1;
+        picked.push(index);
+        sum -= candidates[index].item.calories as usize;
+    }
+    picked.reverse();
+    return picked;
+}
+
+// Every (sum, bitmask) of the subsets of `candidates`, there must be at most 32 of them.
+fn subset_sums(candidates: &[Choice]) -> Vec<(i64, u32)> {
+    let mut sums: Vec<(i64, u32)> = vec![(0, 0)];
+    for (index, choice) in candidates.iter().enumerate() {
+        let calories = choice.item.calories as i64;
+        for i in 0..sums.len() {
+            let (sum, mask) = sums[i];
+            sums.push((sum + calories, mask | (1 << index)));
+        }
+    }
+    return sums;
+}
+
+fn meet_in_the_middle(candidates: &[Choice], target: i64) -> Vec<usize> {
+    let half = candidates.len() / 2;
+    let left = subset_sums(&candidates[..half]);
+    let mut right = subset_sums(&candidates[half..]);
+    right.sort();
+
+    let mut best = (0, 0, 0);
+    for &(left_sum, left_mask) in &left {
+        let wanted = target - left_sum;
+        let position = right.partition_point(|&(sum, _)| sum < wanted);
+        // The best partner is either the first sum >= wanted or the one just before it.
+        for candidate in [position.checked_sub(1), Some(position)]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(&(right_sum, right_mask)) = right.get(candidate) {
+                if is_closer(left_sum + right_sum, best.0, target) {
+                    best = (left_sum + right_sum, left_mask, right_mask);
+                }
+            }
+        }
+    }
+
+    let mut picked: Vec<usize> = Vec::new();
+    for i in 0..half {
+        if best.1 & (1 << i) != 0 {
+            picked.push(i);
+        }
+    }
+    for i in 0..candidates.len() - half {
+        if best.2 & (1 << i) != 0 {
+            picked.push(half + i);
+        }
+    }
+    return picked;
+}
+
+// day1 subset <target> [--elf N] [input]
+pub fn run(args: &[String]) {
+    let mut target: Option<i64> = None;
+    let mut elf: Option<usize> = None;
+    let mut path = "input.txt";
+
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        if arg == "--elf" {
+            let number: usize = args_iter
+                .next()
+                .and_then(|x| x.parse().ok())
+                .expect("--elf needs an elf number");
+            elf = Some(number.checked_sub(1).expect("elves are numbered from 1"));
+        } else if target.is_none() {
+            target = Some(arg.parse().expect("the target must be a number"));
+        } else {
+            path = arg;
+        }
+    }
+
+    let target = target.expect("usage: day1 subset <target> [--elf N] [input]");
+    let content = std::fs::read_to_string(path).expect("correctly read input");
+    let bags = crate::exit_on_error(crate::parse_bags(&content));
+
+    match closest_subset(&bags, target, elf) {
+        Ok(selection) => {
+            for choice in &selection.choices {
+                println!(
+                    "{} line {}: {}",
+                    bags[choice.elf].display_name(choice.elf),
+                    choice.item.line,
+                    choice.item.describe()
+                );
+            }
+            let kind = if selection.is_exact() {
+                "exact"
+            } else {
+                "closest"
+            };
+            println!(
+                "{} total: {} (target {})",
+                kind, selection.total, selection.target
+            );
+        }
+        Err(message) => println!("{}", message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xorshift::XorShift;
+
+    fn choices(calories: &[i32]) -> Vec<Choice> {
+        return calories
+            .iter()
+            .enumerate()
+            .map(|(line, &calories)| Choice {
+                elf: 0,
+                item: Item {
+                    line: line + 1,
+                    calories,
+                    label: None,
+                },
+            })
+            .collect();
+    }
+
+    fn total(candidates: &[Choice], picked: &[usize]) -> i64 {
+        return picked
+            .iter()
+            .map(|&i| candidates[i].item.calories as i64)
+            .sum();
+    }
+
+    fn both(calories: &[i32], target: i64) -> (i64, i64) {
+        let candidates = choices(calories);
+        let biggest = calories.iter().copied().max().unwrap_or(0) as i64;
+        let bound = (target.max(0) + biggest) as usize;
+        let dp = dynamic_programming(&candidates, target, bound);
+        let mitm = meet_in_the_middle(&candidates, target);
+        return (total(&candidates, &dp), total(&candidates, &mitm));
+    }
+
+    #[test]
+    fn dynamic_programming_and_meet_in_the_middle_agree() {
+        let mut rng = XorShift::new(0x2545f4914f6cdd1d);
+        for count in 0..=12 {
+            let calories: Vec<i32> = (0..count).map(|_| rng.below(100) as i32).collect();
+            for target in [-50, 0, 1, 37, 250, 600, 5000] {
+                let (dp, mitm) = both(&calories, target);
+                assert_eq!(dp, mitm, "{:?} target {}", calories, target);
+            }
+        }
+    }
+
+    #[test]
+    fn zero_and_negative_targets_pick_nothing() {
+        assert_eq!(both(&[3, 5, 8], 0), (0, 0));
+        assert_eq!(both(&[3, 5, 8], -10), (0, 0));
+    }
+
+    #[test]
+    fn empty_set() {
+        assert_eq!(both(&[], 10), (0, 0));
+        let selection = closest_subset(&[], 10, None).unwrap();
+        assert!(selection.choices.is_empty());
+        assert_eq!(selection.total, 0);
+    }
+
+    #[test]
+    fn exact_and_closest() {
+        assert_eq!(both(&[1000, 2000, 3000, 4000], 6000), (6000, 6000));
+        // 7 is as far as 11, under budget wins the tie.
+        assert_eq!(both(&[7, 11], 9), (7, 7));
+        assert_eq!(both(&[7, 11], 10), (11, 11));
+    }
+
+    #[test]
+    fn single_elf_reports_where_items_come_from() {
+        let bags = crate::parse_bags("1000\n2000\n3000\n\n4000\n\n5000\n6000").unwrap();
+        let selection = closest_subset(&bags, 6000, Some(2)).unwrap();
+        assert!(selection.is_exact());
+        assert_eq!(selection.total, 6000);
+        assert_eq!(selection.choices.len(), 1);
+        assert_eq!(selection.choices[0].elf, 2);
+        assert_eq!(selection.choices[0].item.line, 8);
+        assert!(closest_subset(&bags, 6000, Some(3)).is_err());
+    }
+}
@@ -0,0 +1,445 @@
+// Does a guide look like someone played it, or like someone wrote it to win? Four classic tests
+// over the decoded rounds:
+//
+// - chi-square: are the shapes played about as often as each other,
+// - runs test: do wins and non wins alternate like coin flips, not in long streaks or strict turns,
+// - win rate: a 95% (Wilson) interval around the win rate, against the chance a player picking
+//   shapes at random would have had against the same elf hands,
+// - serial correlation: does a round's score say anything about the next one's.
+//
+// A p-value below `alpha` (0.001 by default), or a win rate surely above chance, flags the guide.
+// Over thousands of rounds even a small edge gets flagged, which is the point: the puzzle says
+// winning every time would be suspicious.
+
+use crate::rules::Rules;
+use crate::scoring::Scoring;
+use crate::{GameResult, GameRound, Hand, Options};
+
+const EPSILON: f64 = 1e-12;
+
+// z of a two-sided 95% interval.
+const Z_95: f64 = 1.959963984540054;
+
+#[derive(Debug, Clone)]
+pub struct Test {
+    pub name: String,
+    pub statistic: f64,
+    // None when the test can't be run on these rounds (everything equal, too few rounds).
+    pub p_value: Option<f64>,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct WinRate {
+    pub wins: usize,
+    pub rounds: usize,
+    pub low: f64,
+    pub high: f64,
+    // The expected win rate of a uniformly random player against the same elf hands.
+    pub chance: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Audit {
+    pub tests: Vec<Test>,
+    pub win_rate: WinRate,
+    pub flags: Vec<String>,
+}
+
+// ln(Gamma(x)) for x > 0 (Lanczos).
+fn ln_gamma(x: f64) -> f64 {
+    let coefficients = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let mut y = x;
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut series = 1.000000000190015;
+    for c in coefficients {
+        y += 1.0;
+        series += c / y;
+    }
+    return -tmp + (2.5066282746310005 * series / x).ln();
+}
+
+// The regularized upper incomplete gamma function Q(a, x), by its series for small x and its
+// continued fraction otherwise.
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let prefactor = (-x + a * x.ln() - ln_gamma(a)).exp();
+
+    if x < a + 1.0 {
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut ap = a;
+        for _ in 0..1000 {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * EPSILON {
+                break;
+            }
+        }
+        return 1.0 - sum * prefactor;
+    }
+
+    let tiny = 1e-300;
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / tiny;
+    let mut d = 1.0 / b;
+    let mut h = d;
+    for i in 1..1000 {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < tiny {
+            d = tiny;
+        }
+        c = b + an / c;
+        if c.abs() < tiny {
+            c = tiny;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    return prefactor * h;
+}
+
+fn chi_square_p_value(statistic: f64, degrees: usize) -> f64 {
+    return gamma_q(degrees as f64 / 2.0, statistic / 2.0);
+}
+
+// P(|Z| >= |z|) for a standard normal Z.
+fn normal_p_value(z: f64) -> f64 {
+    return gamma_q(0.5, z * z / 2.0);
+}
+
+// Are the counts uniform?
+pub fn chi_square(name: &str, counts: &[usize], rules: &Rules) -> Test {
+    let total: usize = counts.iter().sum();
+    let expected = total as f64 / counts.len() as f64;
+    let statistic: f64 = counts
+        .iter()
+        .map(|&x| (x as f64 - expected).powi(2) / expected.max(EPSILON))
+        .sum();
+    let detail: Vec<String> = rules
+        .hands()
+        .map(|x| format!("{} {}", rules.name(x), counts[x.0]))
+        .collect();
+    return Test {
+        name: name.to_string(),
+        statistic,
+        p_value: (total > 0).then(|| chi_square_p_value(statistic, counts.len() - 1)),
+        detail: detail.join(", "),
+    };
+}
+
+// Wald-Wolfowitz runs test on the won / not won sequence.
+fn runs_test(won: &[bool]) -> Test {
+    let n = won.len() as f64;
+    let wins = won.iter().filter(|x| **x).count() as f64;
+    let others = n - wins;
+    let runs = if won.is_empty() {
+        0
+    } else {
+        1 + won.windows(2).filter(|x| x[0] != x[1]).count()
+    };
+
+    let mut test = Test {
+        name: "runs of wins".to_string(),
+        statistic: 0.0,
+        p_value: None,
+        detail: format!("{} runs", runs),
+    };
+    if wins == 0.0 || others == 0.0 {
+        return test;
+    }
+    let mean = 2.0 * wins * others / n + 1.0;
+    let variance = (mean - 1.0) * (mean - 2.0) / (n - 1.0);
+    if variance <= 0.0 {
+        return test;
+    }
+    test.statistic = (runs as f64 - mean) / variance.sqrt();
+    test.p_value = Some(normal_p_value(test.statistic));
+    test.detail = format!("{} runs, {:.1} expected", runs, mean);
+    return test;
+}
+
+// Lag 1 autocorrelation of the round scores, about N(0, 1/n) for independent rounds.
+fn serial_correlation(scores: &[f64]) -> Test {
+    let n = scores.len();
+    let mut test = Test {
+        name: "serial correlation".to_string(),
+        statistic: 0.0,
+        p_value: None,
+        detail: String::new(),
+    };
+    if n < 3 {
+        return test;
+    }
+    let mean = scores.iter().sum::<f64>() / n as f64;
+    let variance: f64 = scores.iter().map(|x| (x - mean).powi(2)).sum();
+    if variance < EPSILON {
+        return test;
+    }
+    let covariance: f64 = scores
+        .windows(2)
+        .map(|x| (x[0] - mean) * (x[1] - mean))
+        .sum();
+    let r = covariance / variance;
+    test.statistic = r * (n as f64).sqrt();
+    test.p_value = Some(normal_p_value(test.statistic));
+    test.detail = format!("r = {:.4}", r);
+    return test;
+}
+
+// Wilson score interval of the win rate.
+fn win_rate(rounds: &[GameRound], rules: &Rules) -> WinRate {
+    let n = rounds.len();
+    let wins = rounds
+        .iter()
+        .filter(|x| x.my_hand.get_result_against(&x.elf_hand, rules) == GameResult::Win)
+        .count();
+    let chance = rounds
+        .iter()
+        .map(|x| {
+            let beaten_by = rules
+                .hands()
+                .filter(|h| rules.beats(*h, x.elf_hand))
+                .count();
+            beaten_by as f64 / rules.len() as f64
+        })
+        .sum::<f64>()
+        / n.max(1) as f64;
+
+    if n == 0 {
+        return WinRate {
+            wins,
+            rounds: n,
+            low: 0.0,
+            high: 1.0,
+            chance,
+        };
+    }
+    let p = wins as f64 / n as f64;
+    let z2 = Z_95 * Z_95;
+    let denominator = 1.0 + z2 / n as f64;
+    let center = (p + z2 / (2.0 * n as f64)) / denominator;
+    let half = Z_95 * (p * (1.0 - p) / n as f64 + z2 / (4.0 * (n * n) as f64)).sqrt() / denominator;
+    return WinRate {
+        wins,
+        rounds: n,
+        low: center - half,
+        high: center + half,
+        chance,
+    };
+}
+
+pub fn shape_counts(hands: impl Iterator<Item = Hand>, rules: &Rules) -> Vec<usize> {
+    let mut counts = vec![0; rules.len()];
+    for hand in hands {
+        counts[hand.0] += 1;
+    }
+    return counts;
+}
+
+pub fn audit(rounds: &[GameRound], rules: &Rules, scoring: &Scoring, alpha: f64) -> Audit {
+    let won: Vec<bool> = rounds
+        .iter()
+        .map(|x| x.my_hand.get_result_against(&x.elf_hand, rules) == GameResult::Win)
+        .collect();
+    let scores: Vec<f64> = rounds
+        .iter()
+        .map(|x| x.get_game_result(rules, scoring) as f64)
+        .collect();
+
+    let tests = vec![
+        chi_square(
+            "my shapes",
+            &shape_counts(rounds.iter().map(|x| x.my_hand), rules),
+            rules,
+        ),
+        runs_test(&won),
+        serial_correlation(&scores),
+    ];
+    let win_rate = win_rate(rounds, rules);
+
+    let mut flags: Vec<String> = Vec::new();
+    for test in &tests {
+        if let Some(p) = test.p_value.filter(|p| *p < alpha) {
+            flags.push(format!("{} (p = {:.2e})", test.name, p));
+        }
+    }
+    if win_rate.rounds > 0 && win_rate.low > win_rate.chance {
+        flags.push(format!(
+            "wins {:.1}% of rounds, chance is {:.1}%",
+            100.0 * win_rate.wins as f64 / win_rate.rounds as f64,
+            100.0 * win_rate.chance
+        ));
+    }
+    if win_rate.rounds > 0 && win_rate.wins == win_rate.rounds {
+        flags.push("wins every round".to_string());
+    }
+
+    return Audit {
+        tests,
+        win_rate,
+        flags,
+    };
+}
+
+fn format_test(test: &Test) -> String {
+    return match test.p_value {
+        Some(p) => format!(
+            "{}: statistic {:.3}, p = {:.4} ({})",
+            test.name, test.statistic, p, test.detail
+        ),
+        None => format!("{}: not applicable ({})", test.name, test.detail),
+    };
+}
+
+// day2 audit [--alpha a]
+//
+// Exits with 1 when a part of the guide is flagged.
+pub fn run(options: &Options) {
+    let guide = options.guide();
+    let rules = &options.rules;
+    let alpha = options.number::<f64>("--alpha").unwrap_or(0.001);
+
+    let elf = chi_square(
+        "elf shapes",
+        &shape_counts(guide.lines.iter().map(|x| x.elf_hand), rules),
+        rules,
+    );
+    println!("{}", format_test(&elf));
+
+    let mut flagged = false;
+    let guide_parts = [Some(&guide.part1), guide.part2.as_ref()];
+    for (part, rounds) in guide_parts.iter().enumerate() {
+        let Some(rounds) = rounds else {
+            println!();
+            println!("part2: needs every second column to be X, Y or Z");
+            continue;
+        };
+        let audit = audit(rounds, rules, &options.scoring, alpha);
+
+        println!();
+        println!("part{}:", part + 1);
+        for test in &audit.tests {
+            println!("  {}", format_test(test));
+        }
+        let win_rate = &audit.win_rate;
+        println!(
+            "  win rate: {} of {}, 95% interval {:.4} to {:.4}, chance {:.4}",
+            win_rate.wins, win_rate.rounds, win_rate.low, win_rate.high, win_rate.chance
+        );
+        if audit.flags.is_empty() {
+            println!("  looks played");
+        } else {
+            flagged = true;
+            println!("  looks engineered: {}", audit.flags.join("; "));
+        }
+    }
+
+    if flagged {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn assert_near(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{} instead of {}",
+            actual,
+            expected
+        );
+    }
+
+    fn rounds(hands: &[(usize, usize)]) -> Vec<GameRound> {
+        return hands
+            .iter()
+            .enumerate()
+            .map(|(round, &(mine, theirs))| GameRound {
+                my_hand: Hand(mine),
+                elf_hand: Hand(theirs),
+                round,
+            })
+            .collect();
+    }
+
+    #[test]
+    fn known_values() {
+        assert_near(ln_gamma(5.0), 24f64.ln(), 1e-10);
+        assert_near(ln_gamma(0.5), std::f64::consts::PI.sqrt().ln(), 1e-10);
+        assert_near(normal_p_value(Z_95), 0.05, 1e-9);
+        assert_near(normal_p_value(0.0), 1.0, 1e-12);
+        // With 2 degrees of freedom the p-value is exp(-x / 2), with 4 exp(-x / 2) (1 + x / 2).
+        assert_near(chi_square_p_value(6.0, 2), (-3f64).exp(), 1e-9);
+        assert_near(chi_square_p_value(6.0, 2), 0.0498, 1e-4);
+        assert_near(chi_square_p_value(1.0, 2), (-0.5f64).exp(), 1e-9);
+        assert_near(chi_square_p_value(20.0, 4), 11.0 * (-10f64).exp(), 1e-12);
+    }
+
+    #[test]
+    fn wilson_interval() {
+        let rules = Rules::rock_paper_scissors();
+        // Paper beats rock, then two draws.
+        let win_rate = win_rate(&rounds(&[(1, 0), (0, 0), (2, 2)]), &rules);
+        assert_eq!((win_rate.wins, win_rate.rounds), (1, 3));
+        assert_near(win_rate.low, 0.0615, 1e-4);
+        assert_near(win_rate.high, 0.7923, 1e-4);
+        assert_near(win_rate.chance, 1.0 / 3.0, 1e-12);
+    }
+
+    #[test]
+    fn runs_test_catches_strict_turns() {
+        let alternating: Vec<bool> = (0..100).map(|x| x % 2 == 0).collect();
+        let test = runs_test(&alternating);
+        assert!(test.statistic > 0.0);
+        assert!(test.p_value.unwrap() < 0.001);
+
+        // Two long streaks are as suspicious, the other way.
+        let streaks: Vec<bool> = (0..100).map(|x| x < 50).collect();
+        let test = runs_test(&streaks);
+        assert!(test.statistic < 0.0);
+        assert!(test.p_value.unwrap() < 0.001);
+
+        assert_eq!(runs_test(&[true; 10]).p_value, None);
+    }
+
+    #[test]
+    fn flags() {
+        let rules = Rules::rock_paper_scissors();
+        let scoring = Scoring::puzzle(&rules);
+
+        // Always the shape beating the elf's.
+        let winning: Vec<(usize, usize)> = (0..60).map(|x| ((x + 1) % 3, x % 3)).collect();
+        let audit_won = audit(&rounds(&winning), &rules, &scoring, 0.001);
+        assert!(audit_won.flags.contains(&"wins every round".to_string()));
+        assert!(audit_won
+            .flags
+            .iter()
+            .any(|x| x.starts_with("wins 100.0% of rounds")));
+
+        let mut rng = Rng::new(7);
+        let random: Vec<(usize, usize)> = (0..300).map(|_| (rng.below(3), rng.below(3))).collect();
+        let audit_random = audit(&rounds(&random), &rules, &scoring, 0.001);
+        assert!(audit_random.flags.is_empty(), "{:?}", audit_random.flags);
+    }
+}
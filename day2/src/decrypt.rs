@@ -0,0 +1,195 @@
+// Part 1 and part 2 are two guesses at what the second column of the guide means. This tries all of
+// them: every bijection from the second column symbols to the shapes of the game (N! of them), and
+// every bijection from X, Y and Z to the three outcomes when the guide only uses those.
+
+use crate::rules::{Rules, PLAYER_SYMBOLS};
+use crate::scoring::Scoring;
+use crate::{total_score, GameResult, GameRound, Guide, Hand, Options};
+
+// 8! mappings is already 40320 scorings of the guide.
+pub const MAX_SHAPES: usize = 8;
+
+// What each second column symbol stands for, indexed like PLAYER_SYMBOLS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mapping {
+    Shapes(Vec<Hand>),
+    Outcomes(Vec<GameResult>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub mapping: Mapping,
+    pub total: i32,
+}
+
+fn permutations<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+    if items.is_empty() {
+        return vec![Vec::new()];
+    }
+
+    let mut all: Vec<Vec<T>> = Vec::new();
+    for i in 0..items.len() {
+        let mut rest = items.to_vec();
+        let first = rest.remove(i);
+        for mut permutation in permutations(&rest) {
+            permutation.insert(0, first.clone());
+            all.push(permutation);
+        }
+    }
+    return all;
+}
+
+pub fn rounds_for(guide: &Guide, mapping: &Mapping, rules: &Rules) -> Vec<GameRound> {
+    return guide
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(round, line)| {
+            let symbol = line.response.hand.0;
+            let my_hand = match mapping {
+                Mapping::Shapes(hands) => hands[symbol],
+                Mapping::Outcomes(results) => line
+                    .elf_hand
+                    .get_hand_from_game_result(&results[symbol], rules),
+            };
+            GameRound {
+                my_hand,
+                elf_hand: line.elf_hand,
+                round,
+            }
+        })
+        .collect();
+}
+
+// Every mapping with the total it scores, best first.
+pub fn decrypt(guide: &Guide, rules: &Rules, scoring: &Scoring) -> Result<Vec<Candidate>, String> {
+    if rules.len() > MAX_SHAPES {
+        return Err(format!(
+            "{} shapes is too many mappings to try, at most {}",
+            rules.len(),
+            MAX_SHAPES
+        ));
+    }
+
+    let mut mappings: Vec<Mapping> = Vec::new();
+    let hands: Vec<Hand> = rules.hands().collect();
+    for permutation in permutations(&hands) {
+        mappings.push(Mapping::Shapes(permutation));
+    }
+    if guide.lines.iter().all(|line| line.response.hand.0 < 3) {
+        let results = [GameResult::Lost, GameResult::Draw, GameResult::Win];
+        for permutation in permutations(&results) {
+            mappings.push(Mapping::Outcomes(permutation));
+        }
+    }
+
+    let mut candidates: Vec<Candidate> = mappings
+        .into_iter()
+        .map(|mapping| {
+            let total = total_score(&rounds_for(guide, &mapping, rules), rules, scoring);
+            Candidate { mapping, total }
+        })
+        .collect();
+    candidates.sort_by_key(|x| std::cmp::Reverse(x.total));
+    return Ok(candidates);
+}
+
+pub fn describe(mapping: &Mapping, rules: &Rules) -> String {
+    let meanings: Vec<String> = match mapping {
+        Mapping::Shapes(hands) => hands.iter().map(|x| rules.name(*x).to_string()).collect(),
+        Mapping::Outcomes(results) => results.iter().map(|x| format!("{:?}", x)).collect(),
+    };
+    let pairs: Vec<String> = PLAYER_SYMBOLS
+        .chars()
+        .zip(meanings)
+        .map(|(symbol, meaning)| format!("{}={}", symbol, meaning))
+        .collect();
+    return pairs.join(" ");
+}
+
+// day2 decrypt [--total N]
+pub fn run(options: &Options) {
+    let guide = options.guide();
+    let rules = &options.rules;
+    let candidates = crate::exit_on_error(decrypt(&guide, rules, &options.scoring));
+
+    match options.number::<i32>("--total") {
+        Some(total) => {
+            let matching: Vec<&Candidate> =
+                candidates.iter().filter(|x| x.total == total).collect();
+            println!("{} mappings score {}", matching.len(), total);
+            for candidate in matching {
+                println!("{}", describe(&candidate.mapping, rules));
+            }
+        }
+        None => {
+            let best = &candidates[0];
+            println!("best {} {}", best.total, describe(&best.mapping, rules));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_guide;
+
+    fn candidates(guide: &str, rules: &Rules) -> Vec<Candidate> {
+        let guide = parse_guide(guide, rules).unwrap();
+        return decrypt(&guide, rules, &Scoring::puzzle(rules)).unwrap();
+    }
+
+    fn scoring(candidates: &[Candidate], total: i32, rules: &Rules) -> Vec<String> {
+        return candidates
+            .iter()
+            .filter(|x| x.total == total)
+            .map(|x| describe(&x.mapping, rules))
+            .collect();
+    }
+
+    #[test]
+    fn example() {
+        let rules = Rules::rock_paper_scissors();
+        let candidates = candidates(include_str!("../input-test.txt"), &rules);
+        // 3! shape mappings and 3! outcome mappings.
+        assert_eq!(candidates.len(), 12);
+        assert_eq!(candidates[0].total, 24);
+        assert_eq!(
+            describe(&candidates[0].mapping, &rules),
+            "X=Scissors Y=Paper Z=Rock"
+        );
+
+        let fifteen = scoring(&candidates, 15, &rules);
+        assert_eq!(fifteen.len(), 8);
+        assert!(fifteen.contains(&"X=Rock Y=Paper Z=Scissors".to_string()));
+        assert_eq!(
+            scoring(&candidates, 12, &rules),
+            vec!["X=Lost Y=Draw Z=Win".to_string()]
+        );
+    }
+
+    #[test]
+    fn lizard_spock() {
+        let rules = Rules::rock_paper_scissors_lizard_spock();
+        // Every mapping plays each shape once against Spock: 1 + 2 + 3 + 4 + 5 for the shapes, two
+        // wins and a draw.
+        let all_once = candidates("E X\nE Y\nE Z\nE W\nE V\n", &rules);
+        assert_eq!(all_once.len(), 120);
+        assert!(all_once.iter().all(|x| x.total == 30));
+        let mut mappings: Vec<&Mapping> = all_once.iter().map(|x| &x.mapping).collect();
+        mappings.sort_by_key(|x| format!("{:?}", x));
+        mappings.dedup();
+        assert_eq!(mappings.len(), 120);
+
+        // Lizard twice (10 points each) and then Paper or Spock (8 points) score the most, whatever
+        // the other symbols are; so does reading X as a win (with Lizard) and Y as a draw.
+        let candidates = candidates("E X\nE X\nE Y\n", &rules);
+        assert_eq!(candidates.len(), 120 + 6);
+        assert_eq!(candidates[0].total, 28);
+        let best = scoring(&candidates, 28, &rules);
+        assert_eq!(best.len(), 2 * 6 + 1);
+        assert!(best.contains(&"X=Lizard Y=Paper Z=Rock W=Scissors V=Spock".to_string()));
+        assert!(best.contains(&"X=Lizard Y=Spock Z=Rock W=Paper V=Scissors".to_string()));
+        assert!(best.contains(&"X=Win Y=Draw Z=Lost".to_string()));
+    }
+}
@@ -0,0 +1,318 @@
+// The optimal mixed strategy of a game table, found by linear programming.
+//
+// The payoff of a round is my score, and the opponent is assumed to play to keep it as low as
+// possible, so this is the zero-sum game "my score against the elf". Shifting the payoffs to be
+// positive, the elf's optimal strategy comes from
+//
+//     maximize sum(y)  subject to  A y <= 1, y >= 0
+//
+// solved with the simplex method (Bland's rule, so degenerate tables can't make it cycle). The game
+// value is 1 / sum(y), the elf plays y * value, and my optimal strategy is read from the dual
+// values of the constraints.
+//
+// A guide's second column is a mixed strategy too (how often it plays each shape): its
+// exploitability is how far below the game value an opponent knowing those frequencies can push my
+// expected score.
+
+use crate::rules::Rules;
+use crate::scoring::Scoring;
+use crate::{GameResult, Hand, Options};
+
+const EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone)]
+pub struct Equilibrium {
+    // Probability of each shape, indexed by hand.
+    pub mine: Vec<f64>,
+    pub theirs: Vec<f64>,
+    // My expected score per round when both play optimally.
+    pub value: f64,
+    // Other optimal strategies exist for the elf (a tie in the final tableau).
+    pub elf_not_unique: bool,
+    // Other optimal strategies may exist for me (a degenerate final tableau).
+    pub mine_not_unique: bool,
+}
+
+// payoff[i][j] is my score for a round where I play i and the elf plays j.
+pub fn payoff_matrix(rules: &Rules, scoring: &Scoring) -> Vec<Vec<f64>> {
+    return rules
+        .hands()
+        .map(|mine| {
+            rules
+                .hands()
+                .map(|theirs| {
+                    let outcome = mine.get_score_from_hand(&theirs, rules, scoring);
+                    (outcome + scoring.shape_points(mine)) as f64
+                })
+                .collect()
+        })
+        .collect();
+}
+
+pub fn solve(payoff: &[Vec<f64>]) -> Equilibrium {
+    let m = payoff.len();
+    let n = payoff[0].len();
+    let lowest = payoff
+        .iter()
+        .flatten()
+        .cloned()
+        .fold(f64::INFINITY, f64::min);
+    let shift = 1.0 - lowest;
+
+    // Rows: [A + shift | identity | 1], last row: the objective [-1 .. -1 | 0 .. 0 | 0].
+    let width = n + m + 1;
+    let mut tableau: Vec<Vec<f64>> = vec![vec![0.0; width]; m + 1];
+    for i in 0..m {
+        for j in 0..n {
+            tableau[i][j] = payoff[i][j] + shift;
+        }
+        tableau[i][n + i] = 1.0;
+        tableau[i][width - 1] = 1.0;
+    }
+    for x in tableau[m].iter_mut().take(n) {
+        *x = -1.0;
+    }
+    let mut basis: Vec<usize> = (n..n + m).collect();
+
+    while let Some(entering) = (0..width - 1).find(|&j| tableau[m][j] < -EPSILON) {
+        let mut leaving: Option<usize> = None;
+        for i in 0..m {
+            if tableau[i][entering] <= EPSILON {
+                continue;
+            }
+            let ratio = tableau[i][width - 1] / tableau[i][entering];
+            let better = match leaving {
+                None => true,
+                Some(l) => {
+                    let best = tableau[l][width - 1] / tableau[l][entering];
+                    ratio < best - EPSILON || (ratio < best + EPSILON && basis[i] < basis[l])
+                }
+            };
+            if better {
+                leaving = Some(i);
+            }
+        }
+        // The objective is bounded by the constraints, there is always a leaving row.
+        let leaving = leaving.unwrap();
+
+        let pivot = tableau[leaving][entering];
+        for x in tableau[leaving].iter_mut() {
+            *x /= pivot;
+        }
+        let pivot_row = tableau[leaving].clone();
+        for (i, row) in tableau.iter_mut().enumerate() {
+            let factor = row[entering];
+            if i != leaving && factor.abs() > EPSILON {
+                for (x, p) in row.iter_mut().zip(&pivot_row) {
+                    *x -= factor * p;
+                }
+            }
+        }
+        basis[leaving] = entering;
+    }
+
+    let total = tableau[m][width - 1];
+    let scale = 1.0 / total;
+    let mut theirs = vec![0.0; n];
+    for (i, &variable) in basis.iter().enumerate() {
+        if variable < n {
+            theirs[variable] = tableau[i][width - 1] * scale;
+        }
+    }
+    let mine: Vec<f64> = (0..m).map(|i| tableau[m][n + i] * scale).collect();
+
+    let elf_not_unique = (0..n).any(|j| !basis.contains(&j) && tableau[m][j].abs() < EPSILON);
+    let mine_not_unique = (0..m).any(|i| tableau[i][width - 1].abs() < EPSILON);
+
+    return Equilibrium {
+        mine,
+        theirs,
+        value: scale - shift,
+        elf_not_unique,
+        mine_not_unique,
+    };
+}
+
+// The lowest expected score `mine` can be held to, and the elf's shape that does it.
+pub fn guarantee(mine: &[f64], payoff: &[Vec<f64>]) -> (f64, Hand) {
+    let mut worst = (f64::INFINITY, Hand(0));
+    for j in 0..payoff[0].len() {
+        let expected: f64 = (0..payoff.len()).map(|i| mine[i] * payoff[i][j]).sum();
+        if expected < worst.0 - EPSILON {
+            worst = (expected, Hand(j));
+        }
+    }
+    return worst;
+}
+
+// The best expected score against `theirs`, and the shape that gets it.
+pub fn best_response(theirs: &[f64], payoff: &[Vec<f64>]) -> (f64, Hand) {
+    let mut best = (f64::NEG_INFINITY, Hand(0));
+    for (i, row) in payoff.iter().enumerate() {
+        let expected: f64 = row.iter().zip(theirs).map(|(x, p)| x * p).sum();
+        if expected > best.0 + EPSILON {
+            best = (expected, Hand(i));
+        }
+    }
+    return best;
+}
+
+// How often each shape appears in `hands`.
+pub fn frequencies(hands: &[Hand], shapes: usize) -> Vec<f64> {
+    let mut counts = vec![0.0; shapes];
+    for hand in hands {
+        counts[hand.0] += 1.0;
+    }
+    let total = hands.len().max(1) as f64;
+    return counts.into_iter().map(|x| x / total).collect();
+}
+
+fn describe(strategy: &[f64], rules: &Rules) -> String {
+    let parts: Vec<String> = rules
+        .hands()
+        .map(|hand| format!("{} {:.4}", rules.name(hand), strategy[hand.0]))
+        .collect();
+    return parts.join(", ");
+}
+
+// day2 equilibrium
+pub fn run(options: &Options) {
+    let rules = &options.rules;
+    let payoff = payoff_matrix(rules, &options.scoring);
+    let equilibrium = solve(&payoff);
+
+    println!("game value {:.4} per round", equilibrium.value);
+    println!(
+        "my optimal strategy: {}",
+        describe(&equilibrium.mine, rules)
+    );
+    println!(
+        "elf optimal strategy: {}",
+        describe(&equilibrium.theirs, rules)
+    );
+
+    let mut win_chance = 0.0;
+    for mine in rules.hands() {
+        for theirs in rules.hands() {
+            if mine.get_result_against(&theirs, rules) == GameResult::Win {
+                win_chance += equilibrium.mine[mine.0] * equilibrium.theirs[theirs.0];
+            }
+        }
+    }
+    println!("chance to win a round at equilibrium {:.4}", win_chance);
+
+    for (who, strategy) in [("me", &equilibrium.mine), ("the elf", &equilibrium.theirs)] {
+        let unused: Vec<&str> = rules
+            .hands()
+            .filter(|x| strategy[x.0] < EPSILON)
+            .map(|x| rules.name(x))
+            .collect();
+        if !unused.is_empty() {
+            println!("degenerate: never played by {}: {}", who, unused.join(", "));
+        }
+    }
+    if equilibrium.elf_not_unique || equilibrium.mine_not_unique {
+        println!("degenerate: the equilibrium is not unique");
+    }
+
+    let guide = options.guide();
+    let guide_parts = [Some(&guide.part1), guide.part2.as_ref()];
+    for (part, rounds) in guide_parts.iter().enumerate() {
+        let Some(rounds) = rounds else {
+            continue;
+        };
+        let my_hands: Vec<Hand> = rounds.iter().map(|x| x.my_hand).collect();
+        let mine = frequencies(&my_hands, rules.len());
+        let (held_to, counter) = guarantee(&mine, &payoff);
+        println!(
+            "guide part{}: {} can be held to {:.4} per round by always playing {} (exploitability {:.4})",
+            part + 1,
+            describe(&mine, rules),
+            held_to,
+            rules.name(counter),
+            equilibrium.value - held_to
+        );
+    }
+
+    let elf_hands: Vec<Hand> = guide.lines.iter().map(|x| x.elf_hand).collect();
+    let theirs = frequencies(&elf_hands, rules.len());
+    let (best, response) = best_response(&theirs, &payoff);
+    println!(
+        "elf: {} lets {} score {:.4} per round ({:+.4} over the game value)",
+        describe(&theirs, rules),
+        rules.name(response),
+        best,
+        best - equilibrium.value
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(
+                (a - e).abs() < 1e-9,
+                "{:?} instead of {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn standard_game() {
+        // Win 1, draw 0, lose -1, rows and columns in rock, paper, scissors order.
+        let payoff = vec![
+            vec![0.0, -1.0, 1.0],
+            vec![1.0, 0.0, -1.0],
+            vec![-1.0, 1.0, 0.0],
+        ];
+        let equilibrium = solve(&payoff);
+        let third = 1.0 / 3.0;
+        assert_close(&equilibrium.mine, &[third, third, third]);
+        assert_close(&equilibrium.theirs, &[third, third, third]);
+        assert!(equilibrium.value.abs() < 1e-9);
+    }
+
+    #[test]
+    fn asymmetric_payoffs() {
+        // Rock beating scissors pays double: paper gets played twice as often.
+        let payoff = vec![
+            vec![0.0, -1.0, 2.0],
+            vec![1.0, 0.0, -1.0],
+            vec![-2.0, 1.0, 0.0],
+        ];
+        let equilibrium = solve(&payoff);
+        assert_close(&equilibrium.mine, &[0.25, 0.5, 0.25]);
+        assert_close(&equilibrium.theirs, &[0.25, 0.5, 0.25]);
+        assert!(equilibrium.value.abs() < 1e-9);
+
+        // Not square and not zero valued: I play the first row 1/4 of the time for 1.5 a round.
+        let payoff = vec![vec![3.0, 0.0], vec![1.0, 2.0]];
+        let equilibrium = solve(&payoff);
+        assert_close(&equilibrium.mine, &[0.25, 0.75]);
+        assert_close(&equilibrium.theirs, &[0.5, 0.5]);
+        assert!((equilibrium.value - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn puzzle_scoring_is_an_equilibrium() {
+        for rules in [
+            Rules::rock_paper_scissors(),
+            Rules::rock_paper_scissors_lizard_spock(),
+        ] {
+            let payoff = payoff_matrix(&rules, &Scoring::puzzle(&rules));
+            let equilibrium = solve(&payoff);
+            // Neither side can do better by moving away from its strategy.
+            let (held_to, _) = guarantee(&equilibrium.mine, &payoff);
+            let (best, _) = best_response(&equilibrium.theirs, &payoff);
+            assert!((held_to - equilibrium.value).abs() < 1e-9);
+            assert!((best - equilibrium.value).abs() < 1e-9);
+            assert!((equilibrium.mine.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+            assert!((equilibrium.theirs.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,298 @@
+// A byte level evaluator for huge guides. Every `A X` line maps to a score precomputed per
+// (first column, second column) pair, a 3x3 table for the puzzle's rules, without building any
+// `GuideLine` or `GameRound`.
+//
+// The table is filled by the structured path itself, and a line that isn't exactly a letter, a
+// space and a letter (extra spaces, a stray character) goes through `parse_line` too, so both paths
+// always agree, errors included.
+
+use std::time::Instant;
+
+use crate::rng::Rng;
+use crate::rules::{Rules, OPPONENT_SYMBOLS, PLAYER_SYMBOLS};
+use crate::scoring::Scoring;
+use crate::{parse_line, GameRound, Options};
+
+// A byte that isn't a symbol of the rule table.
+const NOT_A_SYMBOL: u8 = u8::MAX;
+
+pub struct ScoreTable {
+    shapes: usize,
+    // The index of a first or second column letter, NOT_A_SYMBOL for anything else.
+    elf_index: [u8; 256],
+    response_index: [u8; 256],
+    // part1[elf * shapes + response] and part2[elf * 3 + response], before the round's multiplier.
+    part1: Vec<i32>,
+    part2: Vec<i32>,
+    // For the lines the table can't read.
+    rules: Rules,
+    scoring: Scoring,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Totals {
+    pub part1: i64,
+    // None when a second column isn't X, Y or Z.
+    pub part2: Option<i64>,
+}
+
+fn symbol_index(symbols: &str, shapes: usize) -> [u8; 256] {
+    let mut index = [NOT_A_SYMBOL; 256];
+    for (i, c) in symbols.bytes().take(shapes).enumerate() {
+        index[c as usize] = i as u8;
+    }
+    return index;
+}
+
+impl ScoreTable {
+    pub fn new(rules: &Rules, scoring: &Scoring) -> ScoreTable {
+        let shapes = rules.len();
+        let mut part1 = vec![0; shapes * shapes];
+        let mut part2 = vec![0; shapes * 3];
+        // A round past the multipliers counts once, the table holds unmultiplied scores.
+        let round = scoring.multipliers.len();
+
+        for (i, elf) in OPPONENT_SYMBOLS.chars().take(shapes).enumerate() {
+            for (j, response) in PLAYER_SYMBOLS.chars().take(shapes).enumerate() {
+                let line = parse_line(&format!("{} {}", elf, response), round, rules).unwrap();
+                let (first, second) = GameRound::from_line(&line, round, rules);
+                part1[i * shapes + j] = first.get_game_result(rules, scoring);
+                if let Some(second) = second {
+                    part2[i * 3 + j] = second.get_game_result(rules, scoring);
+                }
+            }
+        }
+
+        return ScoreTable {
+            shapes,
+            elf_index: symbol_index(OPPONENT_SYMBOLS, shapes),
+            response_index: symbol_index(PLAYER_SYMBOLS, shapes),
+            part1,
+            part2,
+            rules: rules.clone(),
+            scoring: scoring.clone(),
+        };
+    }
+}
+
+// Both parts of a guide in one pass over its bytes.
+pub fn evaluate(bytes: &[u8], table: &ScoreTable) -> Result<Totals, String> {
+    let rules = &table.rules;
+    let scoring = &table.scoring;
+    let mut part1: i64 = 0;
+    let mut part2: i64 = 0;
+    let mut has_part2 = true;
+
+    if bytes.is_empty() {
+        return Ok(Totals {
+            part1: 0,
+            part2: Some(0),
+        });
+    }
+    // A lone newline is still an empty line, as `str::lines` sees it.
+    let bytes = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    for (round, line) in bytes.split(|&x| x == b'\n').enumerate() {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let multiplier = match scoring.multipliers.get(round) {
+            Some(&x) => x as i64,
+            None => 1,
+        };
+
+        let (elf, response) = match line {
+            [elf, b' ', response] => (
+                table.elf_index[*elf as usize],
+                table.response_index[*response as usize],
+            ),
+            _ => (NOT_A_SYMBOL, NOT_A_SYMBOL),
+        };
+        if elf == NOT_A_SYMBOL || response == NOT_A_SYMBOL {
+            // Not the usual shape of a line, let the structured path read it or report it.
+            let text = String::from_utf8_lossy(line);
+            let guide_line = parse_line(&text, round, rules)?;
+            let (first, second) = GameRound::from_line(&guide_line, round, rules);
+            part1 += first.get_game_result(rules, scoring) as i64;
+            match second {
+                Some(x) => part2 += x.get_game_result(rules, scoring) as i64,
+                None => has_part2 = false,
+            }
+            continue;
+        }
+
+        let (elf, response) = (elf as usize, response as usize);
+        part1 += table.part1[elf * table.shapes + response] as i64 * multiplier;
+        if response < 3 {
+            part2 += table.part2[elf * 3 + response] as i64 * multiplier;
+        } else {
+            has_part2 = false;
+        }
+    }
+
+    return Ok(Totals {
+        part1,
+        part2: if has_part2 { Some(part2) } else { None },
+    });
+}
+
+// Both parts the structured way, line by line, without keeping the rounds so a huge guide fits in
+// memory.
+pub fn evaluate_structured(s: &str, rules: &Rules, scoring: &Scoring) -> Result<Totals, String> {
+    let mut part1: i64 = 0;
+    let mut part2: Option<i64> = Some(0);
+    for (round, line) in s.lines().enumerate() {
+        let guide_line = parse_line(line, round, rules)?;
+        let (first, second) = GameRound::from_line(&guide_line, round, rules);
+        part1 += first.get_game_result(rules, scoring) as i64;
+        part2 = match (part2, second) {
+            (Some(total), Some(x)) => Some(total + x.get_game_result(rules, scoring) as i64),
+            _ => None,
+        };
+    }
+    return Ok(Totals { part1, part2 });
+}
+
+// A guide of `rounds` random lines, every second column an outcome letter.
+pub fn generate(rounds: usize, seed: u64) -> Vec<u8> {
+    let mut rng = Rng::new(seed);
+    let mut bytes: Vec<u8> = Vec::with_capacity(rounds * 4);
+    for _ in 0..rounds {
+        bytes.push(b'A' + rng.below(3) as u8);
+        bytes.push(b' ');
+        bytes.push(b'X' + rng.below(3) as u8);
+        bytes.push(b'\n');
+    }
+    return bytes;
+}
+
+fn print_totals(totals: &Totals) {
+    println!("part1 {}", totals.part1);
+    match totals.part2 {
+        Some(total) => println!("part2 {}", total),
+        None => println!("part2 needs every second column to be X, Y or Z"),
+    }
+}
+
+// day2 fast [input]
+pub fn run(options: &Options) {
+    let bytes = std::fs::read(&options.path).unwrap_or_else(|e| {
+        println!("cannot read {}: {}", options.path, e);
+        std::process::exit(1);
+    });
+    let table = ScoreTable::new(&options.rules, &options.scoring);
+    print_totals(&evaluate(&bytes, &table).unwrap_or_else(|e| panic!("{}", e)));
+}
+
+// day2 bench [--rounds n] [--seed s]
+//
+// Times both paths on a generated guide (100 million rounds by default, build with --release) and
+// exits with 1 if they disagree.
+pub fn bench(options: &Options) {
+    let rules = &options.rules;
+    let scoring = &options.scoring;
+    let rounds = options.number::<usize>("--rounds").unwrap_or(100_000_000);
+    let seed = options.number::<u64>("--seed").unwrap_or(0);
+
+    let start = Instant::now();
+    let bytes = generate(rounds, seed);
+    println!("generated {} rounds in {:.2?}", rounds, start.elapsed());
+
+    let start = Instant::now();
+    let text = std::str::from_utf8(&bytes).unwrap();
+    let structured = evaluate_structured(text, rules, scoring).unwrap();
+    let structured_time = start.elapsed();
+    println!("structured {:.2?}", structured_time);
+
+    let start = Instant::now();
+    let table = ScoreTable::new(rules, scoring);
+    let fast = evaluate(&bytes, &table).unwrap();
+    let fast_time = start.elapsed();
+    println!("table {:.2?}", fast_time);
+
+    println!(
+        "speedup {:.1}x",
+        structured_time.as_secs_f64() / fast_time.as_secs_f64().max(1e-9)
+    );
+    print_totals(&fast);
+    if fast != structured {
+        println!("mismatch: the structured path gives {:?}", structured);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn both(
+        text: &str,
+        rules: &Rules,
+        scoring: &Scoring,
+    ) -> (Result<Totals, String>, Result<Totals, String>) {
+        let table = ScoreTable::new(rules, scoring);
+        return (
+            evaluate(text.as_bytes(), &table),
+            evaluate_structured(text, rules, scoring),
+        );
+    }
+
+    #[test]
+    fn test_input() {
+        let rules = Rules::rock_paper_scissors();
+        let scoring = Scoring::puzzle(&rules);
+        let expected = Totals {
+            part1: 15,
+            part2: Some(12),
+        };
+        let (fast, structured) = both("A Y\nB X\nC Z\n", &rules, &scoring);
+        assert_eq!(fast, Ok(expected));
+        assert_eq!(structured, Ok(expected));
+    }
+
+    #[test]
+    fn generated_guides() {
+        let rules = Rules::rock_paper_scissors();
+        for scoring in [
+            Scoring::puzzle(&rules),
+            Scoring::parse("draw = 1\nwin = 10\nmultipliers = 3 1 2 5", &rules).unwrap(),
+        ] {
+            for seed in 0..20 {
+                let bytes = generate(1000, seed);
+                let (fast, structured) =
+                    both(std::str::from_utf8(&bytes).unwrap(), &rules, &scoring);
+                assert!(fast.is_ok());
+                assert_eq!(fast, structured, "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn random_lines() {
+        // Lines of every shape, well formed or not: the table path must give the same totals, or
+        // the same error, as the structured one.
+        let pieces = [
+            "A", "B", "C", "D", "E", "X", "Y", "Z", "W", "V", " ", "  ", "\r", "?",
+        ];
+        for rules in [
+            Rules::rock_paper_scissors(),
+            Rules::rock_paper_scissors_lizard_spock(),
+        ] {
+            let scoring = Scoring::puzzle(&rules);
+            let mut rng = Rng::new(42);
+            for _ in 0..500 {
+                let mut lines: Vec<String> = Vec::new();
+                for _ in 0..rng.below(6) {
+                    let line: String = if rng.below(4) == 0 {
+                        (0..rng.below(5))
+                            .map(|_| pieces[rng.below(pieces.len())])
+                            .collect()
+                    } else {
+                        format!("{} {}", pieces[rng.below(5)], pieces[5 + rng.below(5)])
+                    };
+                    lines.push(line);
+                }
+                let text = lines.join("\n") + "\n";
+                let (fast, structured) = both(&text, &rules, &scoring);
+                assert_eq!(fast, structured, "{:?}", text);
+            }
+        }
+    }
+}
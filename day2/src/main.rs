@@ -1,3 +1,4 @@
+#![allow(clippy::needless_return)]
 // https://adventofcode.com/2022/day/2
 
 /*
@@ -48,148 +49,432 @@ Now that you're correctly decrypting the ultra top secret strategy guide, you wo
 Following the Elf's instructions for the second column, what would your total score be if everything goes exactly according to your strategy guide?
 
 */
-use std::{
-    fs::File,
-    io::{BufReader, Lines, Read},
-};
-
-fn read_input_file() -> String {
-    let mut file = File::open("input.txt").unwrap();
-    let mut fileContent = String::new();
-    file.read_to_string(&mut fileContent);
-    return fileContent;
+mod audit;
+mod decrypt;
+mod equilibrium;
+mod fast;
+mod play;
+mod report;
+mod rng;
+mod rules;
+mod scoring;
+mod strategy;
+mod synth;
+mod tournament;
+
+use std::{collections::HashMap, env, fs, str::FromStr};
+
+use rules::Rules;
+use scoring::Scoring;
+
+// The guide at `path`, the mode stops with the reason when it can't be read as text.
+fn read_input_file(path: &str) -> String {
+    return fs::read_to_string(path).unwrap_or_else(|e| {
+        println!("cannot read {}: {}", path, e);
+        std::process::exit(1);
+    });
 }
 
-#[derive(PartialEq, Eq)]
-enum Hand {
-    Rock,
-    Paper,
-    Scissors,
+// For the modes: prints why the input can't be used and stops.
+fn exit_on_error<T>(result: Result<T, String>) -> T {
+    return result.unwrap_or_else(|message| {
+        println!("{}", message);
+        std::process::exit(1);
+    });
 }
 
-enum GameResult {
+// A shape of the game, the index of its row in the `Rules` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Hand(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
     Draw,
     Lost,
     Win,
 }
 
-struct GameRound {
-    pub myHand: Hand,
-    pub elfHand: Hand,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameRound {
+    pub my_hand: Hand,
+    pub elf_hand: Hand,
+    // Position of the round in the guide, starting at 0.
+    pub round: usize,
 }
 
-impl Hand {
-    fn hand_value(&self) -> i32 {
-        return match self {
-            Hand::Rock => 1,
-            Hand::Paper => 2,
-            Hand::Scissors => 3,
-        };
+// The first column of a guide line, the elf's shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElfHand(pub Hand);
+
+// The second column of a guide line. Part 1 reads it as my shape, part 2 as the outcome the round
+// needs, which only X, Y and Z are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Response {
+    pub hand: Hand,
+    pub result: Option<GameResult>,
+}
+
+// One line of the strategy guide, both columns decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuideLine {
+    pub elf_hand: Hand,
+    pub response: Response,
+}
+
+// The guide read both ways. `part2` is None when a second column isn't an outcome.
+pub struct Guide {
+    pub lines: Vec<GuideLine>,
+    pub part1: Vec<GameRound>,
+    pub part2: Option<Vec<GameRound>>,
+}
+
+// Shape i is the i-th letter of `symbols`.
+fn parse_symbol(s: &str, symbols: &str) -> Option<Hand> {
+    let mut chars = s.chars();
+    let letter = chars.next()?;
+    if chars.next().is_some() {
+        return None;
     }
+    return symbols.chars().position(|x| x == letter).map(Hand);
+}
+
+impl FromStr for GameResult {
+    type Err = String;
 
-    fn get_win_hand(&self) -> Hand {
-        return match self {
-            Hand::Paper => Hand::Scissors,
-            Hand::Rock => Hand::Paper,
-            Hand::Scissors => Hand::Rock,
+    fn from_str(s: &str) -> Result<GameResult, String> {
+        return match s {
+            "X" => Ok(GameResult::Lost),
+            "Y" => Ok(GameResult::Draw),
+            "Z" => Ok(GameResult::Win),
+            _ => Err(format!("cannot parse game result {}", s)),
         };
     }
+}
 
-    fn get_lose_hand(&self) -> Hand {
-        return match self {
-            Hand::Paper => Hand::Rock,
-            Hand::Rock => Hand::Scissors,
-            Hand::Scissors => Hand::Paper,
-        };
+impl FromStr for ElfHand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ElfHand, String> {
+        return parse_symbol(s, rules::OPPONENT_SYMBOLS)
+            .map(ElfHand)
+            .ok_or(format!("cannot parse hand {}", s));
+    }
+}
+
+impl FromStr for Response {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Response, String> {
+        let hand =
+            parse_symbol(s, rules::PLAYER_SYMBOLS).ok_or(format!("cannot parse hand {}", s))?;
+        return Ok(Response {
+            hand,
+            result: s.parse::<GameResult>().ok(),
+        });
     }
+}
 
-    fn get_current_self_value(&self) -> Hand {
-        return match self {
-            Hand::Paper => Hand::Paper,
-            Hand::Rock => Hand::Rock,
-            Hand::Scissors => Hand::Scissors,
-        };
+impl FromStr for GuideLine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<GuideLine, String> {
+        let splitted: Vec<&str> = s.split_whitespace().collect();
+        if splitted.len() != 2 {
+            return Err(format!("expected two columns, got {}", s));
+        }
+
+        let elf_hand: ElfHand = splitted[0].parse()?;
+        return Ok(GuideLine {
+            elf_hand: elf_hand.0,
+            response: splitted[1].parse()?,
+        });
     }
+}
 
-    fn get_hand_from_game_result(&self, wished_result: &str) -> Hand {
+impl Hand {
+    fn hand_value(&self, rules: &Rules) -> i32 {
+        return rules.value(*self);
+    }
+
+    // When several shapes beat this one (RPSLS has two), the one worth the most.
+    fn get_win_hand(&self, rules: &Rules) -> Hand {
+        return rules
+            .hands()
+            .filter(|x| rules.beats(*x, *self))
+            .max_by_key(|x| (x.hand_value(rules), std::cmp::Reverse(x.0)))
+            .unwrap();
+    }
+
+    // When this shape beats several others, the one worth the most.
+    fn get_lose_hand(&self, rules: &Rules) -> Hand {
+        return rules
+            .hands()
+            .filter(|x| rules.beats(*self, *x))
+            .max_by_key(|x| (x.hand_value(rules), std::cmp::Reverse(x.0)))
+            .unwrap();
+    }
+
+    fn get_hand_from_game_result(&self, wished_result: &GameResult, rules: &Rules) -> Hand {
         return match wished_result {
-            "X" => self.get_lose_hand(),
-            "Y" => self.get_current_self_value(),
-            "Z" => self.get_win_hand(),
-            _ => panic!("sadsd"),
+            GameResult::Lost => self.get_lose_hand(rules),
+            GameResult::Draw => *self,
+            GameResult::Win => self.get_win_hand(rules),
         };
     }
 
-    fn get_score_from_hand(&self, other_hand: &Hand) -> i32 {
-        if (self == other_hand) {
-            return 3;
+    fn get_result_against(&self, other_hand: &Hand, rules: &Rules) -> GameResult {
+        if self == other_hand {
+            return GameResult::Draw;
         }
 
-        if (self == &Hand::Paper && other_hand == &Hand::Rock
-            || self == &Hand::Rock && other_hand == &Hand::Scissors
-            || self == &Hand::Scissors && other_hand == &Hand::Paper)
-        {
-            return 6;
+        if rules.beats(*self, *other_hand) {
+            return GameResult::Win;
         }
 
-        return 0;
+        return GameResult::Lost;
+    }
+
+    fn get_score_from_hand(&self, other_hand: &Hand, rules: &Rules, scoring: &Scoring) -> i32 {
+        return scoring.outcome_points(&self.get_result_against(other_hand, rules));
     }
 }
 
 impl GameRound {
-    fn get_other_hand(input: &Vec<&str>) -> Hand {
-        let elf_hand = match input[0] {
-            "A" => Hand::Rock,
-            "B" => Hand::Paper,
-            "C" => Hand::Scissors,
-            _ => panic!("cannot parse hand"),
+    // The round as part 1 reads it, and as part 2 does when the second column is an outcome.
+    fn from_line(line: &GuideLine, round: usize, rules: &Rules) -> (GameRound, Option<GameRound>) {
+        let part1 = GameRound {
+            my_hand: line.response.hand,
+            elf_hand: line.elf_hand,
+            round,
         };
-        return elf_hand;
+        let part2 = line.response.result.map(|result| GameRound {
+            my_hand: line.elf_hand.get_hand_from_game_result(&result, rules),
+            elf_hand: line.elf_hand,
+            round,
+        });
+        return (part1, part2);
+    }
+
+    pub fn get_game_result(&self, rules: &Rules, scoring: &Scoring) -> i32 {
+        let score = self
+            .my_hand
+            .get_score_from_hand(&self.elf_hand, rules, scoring)
+            + scoring.shape_points(self.my_hand);
+        return score * scoring.multiplier(self.round);
     }
+}
+
+// Line `round` of a guide, checked against the rule table.
+fn parse_line(line: &str, round: usize, rules: &Rules) -> Result<GuideLine, String> {
+    let guide_line: GuideLine = line
+        .parse()
+        .map_err(|e| format!("line {}: {}", round + 1, e))?;
+    if guide_line.elf_hand.0 >= rules.len() || guide_line.response.hand.0 >= rules.len() {
+        return Err(format!(
+            "line {}: {} has only {} shapes",
+            round + 1,
+            line,
+            rules.len()
+        ));
+    }
+    return Ok(guide_line);
+}
+
+fn parse_guide(s: &str, rules: &Rules) -> Result<Guide, String> {
+    let mut guide = Guide {
+        lines: Vec::new(),
+        part1: Vec::new(),
+        part2: Some(Vec::new()),
+    };
+
+    for (round, line) in s.lines().enumerate() {
+        let guide_line = parse_line(line, round, rules)?;
+        let (part1, part2) = GameRound::from_line(&guide_line, round, rules);
+        guide.lines.push(guide_line);
+        guide.part1.push(part1);
+        guide.part2 = match (guide.part2, part2) {
+            (Some(mut rounds), Some(round)) => {
+                rounds.push(round);
+                Some(rounds)
+            }
+            _ => None,
+        };
+    }
+
+    return Ok(guide);
+}
+
+fn total_score(rounds: &[GameRound], rules: &Rules, scoring: &Scoring) -> i32 {
+    return rounds
+        .iter()
+        .map(|x| x.get_game_result(rules, scoring))
+        .sum();
+}
+
+// Flags that don't take a value.
+const SWITCHES: &[&str] = &["--json"];
+
+// The options every mode shares (`--rules`, `--scoring` and the guide path) and the flags of the
+// mode itself.
+pub struct Options {
+    pub rules: Rules,
+    pub scoring: Scoring,
+    pub path: String,
+    flags: HashMap<String, String>,
+}
 
-    fn new(gameInput: &str, part2: bool) -> GameRound {
-        let splitted: Vec<&str> = gameInput.split_whitespace().collect();
-        let elf_hand = GameRound::get_other_hand(&splitted);
+impl Options {
+    fn parse(args: &[String]) -> Options {
+        let mut rules = Rules::rock_paper_scissors();
+        let mut scoring_path: Option<&str> = None;
+        let mut path = "input.txt";
+        let mut flags: HashMap<String, String> = HashMap::new();
+
+        let mut args_iter = args.iter();
+        while let Some(arg) = args_iter.next() {
+            if arg == "--rules" {
+                let name = args_iter
+                    .next()
+                    .expect("--rules needs rps, rpsls or a file");
+                rules = Rules::load(name).unwrap_or_else(|e| panic!("invalid rules: {}", e));
+            } else if arg == "--scoring" {
+                scoring_path = Some(args_iter.next().expect("--scoring needs a file"));
+            } else if SWITCHES.contains(&arg.as_str()) {
+                flags.insert(arg.clone(), String::new());
+            } else if arg.starts_with("--") {
+                let value = args_iter
+                    .next()
+                    .unwrap_or_else(|| panic!("{} needs a value", arg));
+                flags.insert(arg.clone(), value.clone());
+            } else {
+                path = arg;
+            }
+        }
 
-        let my_hand = if part2 {
-            elf_hand.get_hand_from_game_result(splitted[1])
-        } else {
-            match splitted[1] {
-                "X" => Hand::Rock,
-                "Y" => Hand::Paper,
-                "Z" => Hand::Scissors,
-                _ => panic!("cannot parse hand"),
+        let scoring = match scoring_path {
+            Some(path) => {
+                Scoring::load(path, &rules).unwrap_or_else(|e| panic!("invalid scoring: {}", e))
             }
+            None => Scoring::puzzle(&rules),
         };
-        // let my_hand = match splitted[1] {
-        //     "X" => Hand::Rock,
-        //     "Y" => Hand::Paper,
-        //     "Z" => Hand::Scissors,
-        //     _ => panic!("cannot parse hand"),
-        // };
-
-        return GameRound {
-            myHand: my_hand,
-            elfHand: elf_hand,
+
+        return Options {
+            rules,
+            scoring,
+            path: path.to_string(),
+            flags,
         };
     }
 
-    pub fn get_game_result(&self) -> i32 {
-        return self.myHand.get_score_from_hand(&self.elfHand) + self.myHand.hand_value();
+    pub fn flag(&self, name: &str) -> Option<&str> {
+        return self.flags.get(name).map(|x| x.as_str());
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        return self.flags.contains_key(name);
+    }
+
+    pub fn number<T: FromStr>(&self, name: &str) -> Option<T> {
+        return self.flag(name).map(|x| {
+            x.parse::<T>()
+                .unwrap_or_else(|_| panic!("{} must be a number", name))
+        });
+    }
+
+    pub fn guide(&self) -> Guide {
+        let file_content = read_input_file(&self.path);
+        return exit_on_error(parse_guide(&file_content, &self.rules));
+    }
+}
+
+fn both_parts(options: &Options) {
+    let guide = options.guide();
+    let rules = &options.rules;
+    let scoring = &options.scoring;
+
+    println!("part1 {}", total_score(&guide.part1, rules, scoring));
+    match &guide.part2 {
+        Some(rounds) => println!("part2 {}", total_score(rounds, rules, scoring)),
+        None => println!("part2 needs every second column to be X, Y or Z"),
     }
 }
 
+// day2 [mode] [--rules rps|rpsls|<file>] [--scoring <file>] [mode flags] [input]
 fn main() {
-    let file_content = read_input_file();
-    let mut all_game_round: Vec<GameRound> = Vec::new();
-    let mut total = 0;
-    for line in file_content.lines() {
-        let game_round = GameRound::new(line, true);
-        //all_game_round.push(game_round);
-        total += game_round.get_game_result();
+    let args: Vec<String> = env::args().collect();
+    let modes = [
+        "audit",
+        "bench",
+        "decrypt",
+        "equilibrium",
+        "fast",
+        "play",
+        "predict",
+        "report",
+        "synth",
+        "tournament",
+    ];
+    let (mode, rest) = match args.get(1) {
+        Some(mode) if modes.contains(&mode.as_str()) => (mode.as_str(), &args[2..]),
+        _ => ("", &args[1..]),
+    };
+    let options = Options::parse(rest);
+
+    match mode {
+        "audit" => audit::run(&options),
+        "bench" => fast::bench(&options),
+        "decrypt" => decrypt::run(&options),
+        "equilibrium" => equilibrium::run(&options),
+        "fast" => fast::run(&options),
+        "play" => play::run(&options),
+        "predict" => strategy::run(&options),
+        "report" => report::run(&options),
+        "synth" => synth::run(&options),
+        "tournament" => tournament::run(&options),
+        _ => both_parts(&options),
     }
+}
 
-    println!("result {} ", total);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns() {
+        assert_eq!("B".parse::<ElfHand>(), Ok(ElfHand(Hand(1))));
+        // Each column has its own letters.
+        assert!("Y".parse::<ElfHand>().is_err());
+        assert!("AB".parse::<ElfHand>().is_err());
+        let response: Response = "Z".parse().unwrap();
+        assert_eq!(response.hand, Hand(2));
+        assert_eq!(response.result, Some(GameResult::Win));
+        assert_eq!("W".parse::<Response>().map(|x| x.result), Ok(None));
+        assert!("A".parse::<Response>().is_err());
+    }
 
-    println!("Hello, world!");
+    #[test]
+    fn malformed_guides() {
+        let rules = Rules::rock_paper_scissors();
+        let error = |s: &str| parse_guide(s, &rules).err();
+        assert_eq!(
+            error("A Y\nX Y\n"),
+            Some("line 2: cannot parse hand X".to_string())
+        );
+        assert_eq!(
+            error("A Y Z\n"),
+            Some("line 1: expected two columns, got A Y Z".to_string())
+        );
+        assert_eq!(
+            error("A W\n"),
+            Some("line 1: A W has only 3 shapes".to_string())
+        );
+        assert_eq!(
+            error("D X\n"),
+            Some("line 1: D X has only 3 shapes".to_string())
+        );
+        // W is a shape of a bigger table, and not an outcome: part 2 can't be played.
+        let guide = parse_guide("A W\nB X\n", &Rules::rock_paper_scissors_lizard_spock()).unwrap();
+        assert_eq!(guide.part1.len(), 2);
+        assert!(guide.part2.is_none());
+    }
 }
@@ -0,0 +1,172 @@
+// Play the game from the terminal: each round I type my hand, the opponent's comes from the guide's
+// first column or from a strategy, and the round is scored like any other.
+//
+// The game reads from any `BufRead` and writes to any `Write`, so piping a script into stdin plays
+// a whole game without a terminal.
+
+use std::io::{self, BufRead, Write};
+
+use crate::rules::{Rules, PLAYER_SYMBOLS};
+use crate::scoring::Scoring;
+use crate::strategy::{from_spec, Strategy};
+use crate::{parse_symbol, GameRound, Hand, Options};
+
+pub enum Opponent {
+    // Plays these hands in order, the game ends after the last one.
+    Guide(Vec<Hand>),
+    Strategy(Box<dyn Strategy>),
+}
+
+impl Opponent {
+    fn next_hand(&mut self, round: usize, rules: &Rules) -> Option<Hand> {
+        return match self {
+            Opponent::Guide(hands) => hands.get(round).copied(),
+            Opponent::Strategy(strategy) => Some(strategy.choose(rules)),
+        };
+    }
+
+    fn observe(&mut self, their_hand: Hand, my_hand: Hand, rules: &Rules) {
+        if let Opponent::Strategy(strategy) = self {
+            strategy.observe(their_hand, my_hand, rules);
+        }
+    }
+}
+
+// A shape name or its second column letter, in any case.
+fn parse_hand(input: &str, rules: &Rules) -> Option<Hand> {
+    let input = input.trim();
+    return rules
+        .hands()
+        .find(|x| rules.name(*x).eq_ignore_ascii_case(input))
+        .or_else(|| parse_symbol(&input.to_ascii_uppercase(), PLAYER_SYMBOLS))
+        .filter(|x| x.0 < rules.len());
+}
+
+// Plays until the input ends, `quit` is typed or the guide runs out, and returns the total score.
+pub fn play<R: BufRead, W: Write>(
+    input: R,
+    output: &mut W,
+    opponent: &mut Opponent,
+    rules: &Rules,
+    scoring: &Scoring,
+) -> io::Result<i32> {
+    let names: Vec<&str> = rules.hands().map(|x| rules.name(x)).collect();
+    let mut lines = input.lines();
+    let mut total = 0;
+    let mut round = 0;
+
+    while let Some(elf_hand) = opponent.next_hand(round, rules) {
+        write!(output, "round {} ({}): ", round + 1, names.join("/"))?;
+        output.flush()?;
+
+        let my_hand = loop {
+            let Some(line) = lines.next() else {
+                writeln!(output)?;
+                writeln!(output, "total {}", total)?;
+                return Ok(total);
+            };
+            let line = line?;
+            if line.trim() == "quit" {
+                writeln!(output, "total {}", total)?;
+                return Ok(total);
+            }
+            match parse_hand(&line, rules) {
+                Some(hand) => break hand,
+                None => write!(output, "unknown hand {}, try again: ", line.trim())?,
+            }
+        };
+
+        opponent.observe(my_hand, elf_hand, rules);
+        let game_round = GameRound {
+            my_hand,
+            elf_hand,
+            round,
+        };
+        let score = game_round.get_game_result(rules, scoring);
+        total += score;
+        writeln!(
+            output,
+            "{} vs {}: {:?}, +{} (total {})",
+            rules.name(my_hand),
+            rules.name(elf_hand),
+            my_hand.get_result_against(&elf_hand, rules),
+            score,
+            total
+        )?;
+        round += 1;
+    }
+
+    writeln!(output, "total {}", total)?;
+    return Ok(total);
+}
+
+// day2 play [--opponent guide|<strategy>] [--seed n]
+pub fn run(options: &Options) {
+    let rules = &options.rules;
+    let mut opponent = match options.flag("--opponent").unwrap_or("guide") {
+        "guide" => {
+            let guide = options.guide();
+            Opponent::Guide(guide.lines.iter().map(|x| x.elf_hand).collect())
+        }
+        spec => {
+            let seed = options.number::<u64>("--seed").unwrap_or(0);
+            Opponent::Strategy(from_spec(spec, seed, rules).unwrap_or_else(|e| panic!("{}", e)))
+        }
+    };
+
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    play(
+        stdin.lock(),
+        &mut stdout,
+        &mut opponent,
+        rules,
+        &options.scoring,
+    )
+    .expect("correctly read and write the terminal");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_guide;
+
+    // Plays `script` against the example guide's first column, returns the total and the output.
+    fn play_example(script: &str) -> (i32, String) {
+        let rules = Rules::rock_paper_scissors();
+        let scoring = Scoring::puzzle(&rules);
+        let guide = parse_guide(include_str!("../input-test.txt"), &rules).unwrap();
+        let mut opponent = Opponent::Guide(guide.lines.iter().map(|x| x.elf_hand).collect());
+        let mut output: Vec<u8> = Vec::new();
+        let total = play(
+            io::Cursor::new(script),
+            &mut output,
+            &mut opponent,
+            &rules,
+            &scoring,
+        )
+        .unwrap();
+        return (total, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn whole_guide() {
+        // The elf plays rock, paper, scissors: three draws, 4 + 5 + 6.
+        let (total, output) = play_example("rock\nlizard\npaper\nZ\n");
+        assert_eq!(total, 15);
+        assert!(output.contains("unknown hand lizard, try again: "));
+        assert!(output.contains("Scissors vs Scissors: Draw, +6 (total 15)"));
+        assert!(output.ends_with("total 15\n"));
+    }
+
+    #[test]
+    fn stops_early() {
+        let (total, output) = play_example("rock\nquit\npaper\n");
+        assert_eq!(total, 4);
+        assert!(output.ends_with("total 4\n"));
+
+        let (total, output) = play_example("rock\npaper\n");
+        assert_eq!(total, 9);
+        assert!(output.ends_with("round 3 (Rock/Paper/Scissors): \ntotal 9\n"));
+    }
+}
@@ -0,0 +1,247 @@
+// A round by round breakdown of a guide, with a summary at the end.
+
+use crate::rules::Rules;
+use crate::scoring::Scoring;
+use crate::{GameResult, GameRound, Hand, Options};
+
+#[derive(Debug, Clone)]
+pub struct RoundLine {
+    pub round: usize,
+    pub elf_hand: Hand,
+    pub my_hand: Hand,
+    pub result: GameResult,
+    pub shape_points: i32,
+    pub outcome_points: i32,
+    pub multiplier: i32,
+    pub score: i32,
+    pub running_score: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    // How many times each shape was played, indexed by hand.
+    pub my_shapes: Vec<usize>,
+    pub elf_shapes: Vec<usize>,
+    pub total: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub rounds: Vec<RoundLine>,
+    pub summary: Summary,
+}
+
+pub fn report(rounds: &[GameRound], rules: &Rules, scoring: &Scoring) -> Report {
+    let mut lines: Vec<RoundLine> = Vec::new();
+    let mut summary = Summary {
+        wins: 0,
+        draws: 0,
+        losses: 0,
+        my_shapes: vec![0; rules.len()],
+        elf_shapes: vec![0; rules.len()],
+        total: 0,
+    };
+
+    for game_round in rounds {
+        let result = game_round
+            .my_hand
+            .get_result_against(&game_round.elf_hand, rules);
+        let score = game_round.get_game_result(rules, scoring);
+        summary.total += score;
+        match result {
+            GameResult::Win => summary.wins += 1,
+            GameResult::Draw => summary.draws += 1,
+            GameResult::Lost => summary.losses += 1,
+        }
+        summary.my_shapes[game_round.my_hand.0] += 1;
+        summary.elf_shapes[game_round.elf_hand.0] += 1;
+
+        lines.push(RoundLine {
+            round: game_round.round,
+            elf_hand: game_round.elf_hand,
+            my_hand: game_round.my_hand,
+            result,
+            shape_points: scoring.shape_points(game_round.my_hand),
+            outcome_points: scoring.outcome_points(&result),
+            multiplier: scoring.multiplier(game_round.round),
+            score,
+            running_score: summary.total,
+        });
+    }
+
+    return Report {
+        rounds: lines,
+        summary,
+    };
+}
+
+pub fn to_table(report: &Report, rules: &Rules) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    lines.push(format!(
+        "{:>6} {:>10} {:>10} {:>7} {:>6} {:>8} {:>5} {:>6} {:>8}",
+        "round", "elf", "me", "outcome", "shape", "outcome", "mult", "score", "running"
+    ));
+    for line in &report.rounds {
+        lines.push(format!(
+            "{:>6} {:>10} {:>10} {:>7} {:>6} {:>8} {:>5} {:>6} {:>8}",
+            line.round + 1,
+            rules.name(line.elf_hand),
+            rules.name(line.my_hand),
+            format!("{:?}", line.result),
+            line.shape_points,
+            line.outcome_points,
+            line.multiplier,
+            line.score,
+            line.running_score
+        ));
+    }
+
+    let summary = &report.summary;
+    lines.push(String::new());
+    lines.push(format!(
+        "wins {} draws {} losses {}",
+        summary.wins, summary.draws, summary.losses
+    ));
+    for hand in rules.hands() {
+        lines.push(format!(
+            "{}: played {} faced {}",
+            rules.name(hand),
+            summary.my_shapes[hand.0],
+            summary.elf_shapes[hand.0]
+        ));
+    }
+    lines.push(format!("total {}", summary.total));
+    return lines.join("\n");
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    return out;
+}
+
+pub fn to_json(report: &Report, rules: &Rules) -> String {
+    let rounds: Vec<String> = report
+        .rounds
+        .iter()
+        .map(|line| {
+            format!(
+                "{{\"round\":{},\"elf\":{},\"me\":{},\"outcome\":\"{:?}\",\"shape_points\":{},\"outcome_points\":{},\"multiplier\":{},\"score\":{},\"running_score\":{}}}",
+                line.round + 1,
+                json_string(rules.name(line.elf_hand)),
+                json_string(rules.name(line.my_hand)),
+                line.result,
+                line.shape_points,
+                line.outcome_points,
+                line.multiplier,
+                line.score,
+                line.running_score
+            )
+        })
+        .collect();
+
+    let summary = &report.summary;
+    let shapes: Vec<String> = rules
+        .hands()
+        .map(|hand| {
+            format!(
+                "{}:{{\"played\":{},\"faced\":{}}}",
+                json_string(rules.name(hand)),
+                summary.my_shapes[hand.0],
+                summary.elf_shapes[hand.0]
+            )
+        })
+        .collect();
+
+    return format!(
+        "{{\"rounds\":[{}],\"wins\":{},\"draws\":{},\"losses\":{},\"shapes\":{{{}}},\"total\":{}}}",
+        rounds.join(","),
+        summary.wins,
+        summary.draws,
+        summary.losses,
+        shapes.join(","),
+        summary.total
+    );
+}
+
+// day2 report [--part 1|2] [--json]
+pub fn run(options: &Options) {
+    let guide = options.guide();
+    let rules = &options.rules;
+    let rounds = crate::exit_on_error(match options.number::<u32>("--part").unwrap_or(1) {
+        1 => Ok(guide.part1),
+        2 => guide
+            .part2
+            .ok_or("part2 needs every second column to be X, Y or Z".to_string()),
+        _ => Err("--part must be 1 or 2".to_string()),
+    });
+
+    let report = report(&rounds, rules, &options.scoring);
+    if options.has("--json") {
+        println!("{}", to_json(&report, rules));
+    } else {
+        println!("{}", to_table(&report, rules));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_guide;
+
+    #[test]
+    fn example() {
+        let rules = Rules::rock_paper_scissors();
+        let scoring = Scoring::puzzle(&rules);
+        let guide = parse_guide(include_str!("../input-test.txt"), &rules).unwrap();
+
+        let part1 = report(&guide.part1, &rules, &scoring);
+        let points: Vec<(i32, i32, i32, i32)> = part1
+            .rounds
+            .iter()
+            .map(|x| (x.shape_points, x.outcome_points, x.score, x.running_score))
+            .collect();
+        assert_eq!(points, vec![(2, 6, 8, 8), (1, 0, 1, 9), (3, 3, 6, 15)]);
+        let summary = &part1.summary;
+        assert_eq!((summary.wins, summary.draws, summary.losses), (1, 1, 1));
+        assert_eq!(summary.my_shapes, vec![1, 1, 1]);
+        assert_eq!(summary.elf_shapes, vec![1, 1, 1]);
+        assert_eq!(summary.total, 15);
+
+        let part2 = report(&guide.part2.unwrap(), &rules, &scoring);
+        assert_eq!(part2.summary.total, 12);
+        // Rock draws with rock, loses to paper and beats scissors.
+        assert_eq!(part2.summary.my_shapes, vec![3, 0, 0]);
+    }
+
+    #[test]
+    fn json() {
+        let rules = Rules::rock_paper_scissors();
+        let scoring = Scoring::parse("multipliers = 2", &rules).unwrap();
+        let guide = parse_guide("A Y\nC X\n", &rules).unwrap();
+        let report = report(&guide.part1, &rules, &scoring);
+        assert_eq!(
+            to_json(&report, &rules),
+            "{\"rounds\":[\
+             {\"round\":1,\"elf\":\"Rock\",\"me\":\"Paper\",\"outcome\":\"Win\",\"shape_points\":2,\
+             \"outcome_points\":6,\"multiplier\":2,\"score\":16,\"running_score\":16},\
+             {\"round\":2,\"elf\":\"Scissors\",\"me\":\"Rock\",\"outcome\":\"Win\",\"shape_points\":1,\
+             \"outcome_points\":6,\"multiplier\":1,\"score\":7,\"running_score\":23}],\
+             \"wins\":2,\"draws\":0,\"losses\":0,\
+             \"shapes\":{\"Rock\":{\"played\":1,\"faced\":1},\"Paper\":{\"played\":1,\"faced\":0},\
+             \"Scissors\":{\"played\":0,\"faced\":1}},\"total\":23}"
+        );
+        assert_eq!(json_string("a\"b\\\n"), "\"a\\\"b\\\\\\u000a\"");
+    }
+}
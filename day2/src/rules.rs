@@ -0,0 +1,235 @@
+// The rule table of a cyclic game: its shapes, what each shape is worth and who beats whom.
+//
+// A table is written one shape per line, with its value and the shapes it beats:
+//
+// Rock 1 Scissors
+// Paper 2 Rock
+// Scissors 3 Paper
+//
+// Shape i is written in the guide with the i-th letter of OPPONENT_SYMBOLS in the first column and
+// of PLAYER_SYMBOLS in the second one, which gives the puzzle's A/B/C and X/Y/Z for three shapes.
+
+use crate::Hand;
+
+pub const OPPONENT_SYMBOLS: &str = "ABCDEFGHIJKLM";
+pub const PLAYER_SYMBOLS: &str = "XYZWVUTSRQPON";
+pub const MAX_SHAPES: usize = 13;
+
+const ROCK_PAPER_SCISSORS: &str = "\
+Rock 1 Scissors
+Paper 2 Rock
+Scissors 3 Paper
+";
+
+// Player symbols: Rock X, Paper Y, Scissors Z, Lizard W, Spock V.
+const ROCK_PAPER_SCISSORS_LIZARD_SPOCK: &str = "\
+Rock 1 Scissors Lizard
+Paper 2 Rock Spock
+Scissors 3 Paper Lizard
+Lizard 4 Paper Spock
+Spock 5 Rock Scissors
+";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    names: Vec<String>,
+    values: Vec<i32>,
+    // beats[a][b] is true when shape a beats shape b.
+    beats: Vec<Vec<bool>>,
+}
+
+impl Rules {
+    pub fn rock_paper_scissors() -> Rules {
+        return Rules::parse(ROCK_PAPER_SCISSORS).unwrap();
+    }
+
+    pub fn rock_paper_scissors_lizard_spock() -> Rules {
+        return Rules::parse(ROCK_PAPER_SCISSORS_LIZARD_SPOCK).unwrap();
+    }
+
+    // `rps`, `rpsls` or the path of a rule table file.
+    pub fn load(name: &str) -> Result<Rules, String> {
+        return match name {
+            "rps" => Ok(Rules::rock_paper_scissors()),
+            "rpsls" => Ok(Rules::rock_paper_scissors_lizard_spock()),
+            path => {
+                let content = std::fs::read_to_string(path)
+                    .map_err(|e| format!("cannot read {}: {}", path, e))?;
+                Rules::parse(&content)
+            }
+        };
+    }
+
+    pub fn parse(s: &str) -> Result<Rules, String> {
+        let lines: Vec<Vec<&str>> = s
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<&str>>())
+            .filter(|words| !words.is_empty() && !words[0].starts_with('#'))
+            .collect();
+
+        let names: Vec<String> = lines.iter().map(|words| words[0].to_string()).collect();
+        let mut values: Vec<i32> = Vec::new();
+        let mut beats: Vec<Vec<bool>> = vec![vec![false; names.len()]; names.len()];
+
+        for (shape, words) in lines.iter().enumerate() {
+            let value = words
+                .get(1)
+                .and_then(|x| x.parse::<i32>().ok())
+                .ok_or(format!("{} needs a value", names[shape]))?;
+            values.push(value);
+
+            for beaten in &words[2..] {
+                let other = names
+                    .iter()
+                    .position(|name| name == beaten)
+                    .ok_or(format!("{} beats unknown shape {}", names[shape], beaten))?;
+                beats[shape][other] = true;
+            }
+        }
+
+        let rules = Rules {
+            names,
+            values,
+            beats,
+        };
+        rules.validate()?;
+        return Ok(rules);
+    }
+
+    // Every round must have exactly one outcome, and every outcome must be reachable against every
+    // shape, otherwise "you need to win" can't be followed.
+    fn validate(&self) -> Result<(), String> {
+        let n = self.len();
+        if !(3..=MAX_SHAPES).contains(&n) {
+            return Err(format!("a game needs between 3 and {} shapes", MAX_SHAPES));
+        }
+
+        // Before the pairs, which would report a shape declared twice as a pair of itself.
+        for a in 0..n {
+            if self.names[..a].contains(&self.names[a]) {
+                return Err(format!("{} is declared twice", self.names[a]));
+            }
+        }
+
+        for a in 0..n {
+            if self.beats[a][a] {
+                return Err(format!("{} cannot beat itself", self.names[a]));
+            }
+            for b in 0..n {
+                if a != b && self.beats[a][b] == self.beats[b][a] {
+                    return Err(format!(
+                        "exactly one of {} and {} must beat the other",
+                        self.names[a], self.names[b]
+                    ));
+                }
+            }
+            if !self.beats[a].contains(&true) {
+                return Err(format!("{} beats nothing", self.names[a]));
+            }
+            if !(0..n).any(|b| self.beats[b][a]) {
+                return Err(format!("nothing beats {}", self.names[a]));
+            }
+        }
+
+        return Ok(());
+    }
+
+    pub fn len(&self) -> usize {
+        return self.names.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.names.is_empty();
+    }
+
+    pub fn hands(&self) -> impl Iterator<Item = Hand> {
+        return (0..self.len()).map(Hand);
+    }
+
+    pub fn name(&self, hand: Hand) -> &str {
+        return &self.names[hand.0];
+    }
+
+    pub fn value(&self, hand: Hand) -> i32 {
+        return self.values[hand.0];
+    }
+
+    pub fn beats(&self, hand: Hand, other: Hand) -> bool {
+        return self.beats[hand.0][other.0];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::Scoring;
+    use crate::{parse_guide, total_score};
+
+    #[test]
+    fn builtin_tables() {
+        let rules = Rules::rock_paper_scissors_lizard_spock();
+        assert_eq!(rules.len(), 5);
+        for hand in rules.hands() {
+            let beaten = rules.hands().filter(|x| rules.beats(hand, *x)).count();
+            assert_eq!(beaten, 2, "{}", rules.name(hand));
+        }
+        // Comments and blank lines are skipped.
+        let parsed =
+            Rules::parse("# the puzzle\n\nRock 1 Scissors\nPaper 2 Rock\n\nScissors 3 Paper\n");
+        assert_eq!(parsed, Ok(Rules::rock_paper_scissors()));
+    }
+
+    #[test]
+    fn rejected_tables() {
+        for (table, error) in [
+            (
+                "Rock 1 Paper\nPaper 2 Rock\n",
+                "a game needs between 3 and 13 shapes",
+            ),
+            (
+                "Rock 1 Scissors\nPaper 2 Rock\nScissors 3 Paper\nRock 4 Paper\n",
+                "Rock is declared twice",
+            ),
+            (
+                "Rock 1 Rock Scissors\nPaper 2 Rock\nScissors 3 Paper\n",
+                "Rock cannot beat itself",
+            ),
+            (
+                "Rock 1 Scissors Paper\nPaper 2 Rock\nScissors 3 Paper\n",
+                "exactly one of Rock and Paper must beat the other",
+            ),
+            (
+                "Rock 1 Scissors\nPaper 2\nScissors 3 Paper\n",
+                "exactly one of Rock and Paper must beat the other",
+            ),
+            (
+                "Rock 1\nPaper 2 Rock Scissors\nScissors 3 Rock\n",
+                "Rock beats nothing",
+            ),
+            (
+                "Rock 1 Scissors\nPaper Rock\nScissors 3 Paper\n",
+                "Paper needs a value",
+            ),
+            (
+                "Rock 1 Scissors\nPaper 2 Rock\nScissors 3 Paper Well\n",
+                "Scissors beats unknown shape Well",
+            ),
+        ] {
+            assert_eq!(Rules::parse(table), Err(error.to_string()), "{:?}", table);
+        }
+    }
+
+    #[test]
+    fn lizard_spock_guide() {
+        let rules = Rules::rock_paper_scissors_lizard_spock();
+        let scoring = Scoring::puzzle(&rules);
+        let guide = parse_guide("A X\nE Z\nD Y\n", &rules).unwrap();
+        // Rock draws with rock, Scissors loses to Spock, Paper loses to Lizard.
+        assert_eq!(total_score(&guide.part1, &rules, &scoring), 4 + 3 + 2);
+        // Lizard loses to rock and beats Spock, the most valuable shape each time, then a draw.
+        let part2 = guide.part2.unwrap();
+        let mine: Vec<&str> = part2.iter().map(|x| rules.name(x.my_hand)).collect();
+        assert_eq!(mine, vec!["Lizard", "Lizard", "Lizard"]);
+        assert_eq!(total_score(&part2, &rules, &scoring), 4 + 10 + 7);
+    }
+}
@@ -0,0 +1,170 @@
+// How a round is scored: the points of the shape played, the points of the outcome, and an optional
+// multiplier per round.
+//
+// A scoring config starts from the puzzle's profile (the shape values of the rule table, 0 for a
+// loss, 3 for a draw and 6 for a win) and overrides what it sets, one `key = value` per line:
+//
+// lost = 0
+// draw = 1
+// win = 3
+// Rock = 2
+// multipliers = 1 1 2
+//
+// Round i (starting at 0) is multiplied by the i-th multiplier, rounds past the end of the list
+// count once.
+
+use crate::rules::Rules;
+use crate::{GameResult, Hand};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scoring {
+    pub shape_values: Vec<i32>,
+    pub lost: i32,
+    pub draw: i32,
+    pub win: i32,
+    pub multipliers: Vec<i32>,
+}
+
+impl Scoring {
+    pub fn puzzle(rules: &Rules) -> Scoring {
+        return Scoring {
+            shape_values: rules.hands().map(|x| rules.value(x)).collect(),
+            lost: 0,
+            draw: 3,
+            win: 6,
+            multipliers: Vec::new(),
+        };
+    }
+
+    pub fn load(path: &str, rules: &Rules) -> Result<Scoring, String> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+        return Scoring::parse(&content, rules);
+    }
+
+    pub fn parse(s: &str, rules: &Rules) -> Result<Scoring, String> {
+        let mut scoring = Scoring::puzzle(rules);
+
+        for line in s.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or(format!("expected `key = value`, got {}", line))?;
+
+            let number = || {
+                value
+                    .parse::<i32>()
+                    .map_err(|_| format!("{} must be a number", key))
+            };
+            match key {
+                "lost" => scoring.lost = number()?,
+                "draw" => scoring.draw = number()?,
+                "win" => scoring.win = number()?,
+                "multipliers" => {
+                    scoring.multipliers = value
+                        .split_whitespace()
+                        .map(|x| x.parse::<i32>())
+                        .collect::<Result<Vec<i32>, _>>()
+                        .map_err(|_| "multipliers must be numbers".to_string())?;
+                }
+                shape => {
+                    let hand = rules
+                        .hands()
+                        .find(|x| rules.name(*x) == shape)
+                        .ok_or(format!("unknown setting {}", shape))?;
+                    scoring.shape_values[hand.0] = number()?;
+                }
+            }
+        }
+
+        return Ok(scoring);
+    }
+
+    pub fn shape_points(&self, hand: Hand) -> i32 {
+        return self.shape_values[hand.0];
+    }
+
+    pub fn outcome_points(&self, result: &GameResult) -> i32 {
+        return match result {
+            GameResult::Lost => self.lost,
+            GameResult::Draw => self.draw,
+            GameResult::Win => self.win,
+        };
+    }
+
+    pub fn multiplier(&self, round: usize) -> i32 {
+        return self.multipliers.get(round).copied().unwrap_or(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_guide, total_score};
+
+    #[test]
+    fn overrides() {
+        let rules = Rules::rock_paper_scissors();
+        let scoring = Scoring::parse("# comment\n\nwin = 10\n  Rock=5  \nlost = -1\n", &rules);
+        assert_eq!(
+            scoring,
+            Ok(Scoring {
+                shape_values: vec![5, 2, 3],
+                lost: -1,
+                draw: 3,
+                win: 10,
+                multipliers: Vec::new(),
+            })
+        );
+
+        let rules = Rules::rock_paper_scissors_lizard_spock();
+        let scoring = Scoring::parse("Spock = 0\n", &rules).unwrap();
+        assert_eq!(scoring.shape_values, vec![1, 2, 3, 4, 0]);
+    }
+
+    #[test]
+    fn multipliers_per_round() {
+        let rules = Rules::rock_paper_scissors();
+        let scoring = Scoring::parse("multipliers = 2 0\nwin = 10\nRock = 5\n", &rules).unwrap();
+        assert_eq!(scoring.multiplier(0), 2);
+        assert_eq!(scoring.multiplier(1), 0);
+        // Past the end of the list a round counts once.
+        assert_eq!(scoring.multiplier(2), 1);
+        assert_eq!(scoring.multiplier(100), 1);
+
+        let guide = parse_guide("A Y\nB X\nC Z\n", &rules).unwrap();
+        let scores: Vec<i32> = guide
+            .part1
+            .iter()
+            .map(|x| x.get_game_result(&rules, &scoring))
+            .collect();
+        // Paper beats rock twice, rock loses to paper for nothing, then a draw counted once.
+        assert_eq!(scores, vec![(2 + 10) * 2, 0, 3 + 3]);
+        assert_eq!(total_score(&guide.part1, &rules, &scoring), 30);
+    }
+
+    #[test]
+    fn rejected_configs() {
+        let rules = Rules::rock_paper_scissors();
+        for (config, error) in [
+            ("speed = 3\n", "unknown setting speed"),
+            ("Lizard = 4\n", "unknown setting Lizard"),
+            ("win = three\n", "win must be a number"),
+            ("draw =\n", "draw must be a number"),
+            ("Rock = 1.5\n", "Rock must be a number"),
+            ("multipliers = 1 x 2\n", "multipliers must be numbers"),
+            ("win 3\n", "expected `key = value`, got win 3"),
+        ] {
+            assert_eq!(
+                Scoring::parse(config, &rules),
+                Err(error.to_string()),
+                "{:?}",
+                config
+            );
+        }
+    }
+}
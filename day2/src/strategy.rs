@@ -0,0 +1,353 @@
+// Strategies that don't read the second column of the guide. The predicting ones only see the hands
+// the elf has already played, try to guess the next one and play what beats it.
+//
+// New strategies plug in by implementing `Strategy`. Ties in a prediction always go to the first
+// shape of the rule table so a game is reproducible.
+
+use std::collections::HashMap;
+
+use crate::rng::Rng;
+use crate::rules::Rules;
+use crate::scoring::Scoring;
+use crate::{total_score, GameResult, GameRound, Hand, Options};
+
+pub trait Strategy {
+    fn name(&self) -> String;
+
+    // My hand for the next round, before seeing the opponent's.
+    fn choose(&mut self, rules: &Rules) -> Hand;
+
+    // Called once the round has been played.
+    fn observe(&mut self, their_hand: Hand, my_hand: Hand, rules: &Rules);
+}
+
+// The most common hand in `counts`, None when nothing was counted yet.
+fn most_common(counts: &[usize]) -> Option<Hand> {
+    let mut best: Option<Hand> = None;
+    for (i, &count) in counts.iter().enumerate() {
+        if count > 0 && best.is_none_or(|x| count > counts[x.0]) {
+            best = Some(Hand(i));
+        }
+    }
+    return best;
+}
+
+// Plays against the hand the opponent played the most so far.
+#[derive(Default)]
+pub struct Frequency {
+    counts: Vec<usize>,
+}
+
+impl Strategy for Frequency {
+    fn name(&self) -> String {
+        return "frequency".to_string();
+    }
+
+    fn choose(&mut self, rules: &Rules) -> Hand {
+        return match most_common(&self.counts) {
+            Some(predicted) => predicted.get_win_hand(rules),
+            None => Hand(0),
+        };
+    }
+
+    fn observe(&mut self, their_hand: Hand, _my_hand: Hand, rules: &Rules) {
+        self.counts.resize(rules.len(), 0);
+        self.counts[their_hand.0] += 1;
+    }
+}
+
+// Plays against the hand that most often followed the opponent's last `order` hands, falling back
+// to the overall frequency for a history it hasn't seen yet.
+pub struct Markov {
+    order: usize,
+    history: Vec<Hand>,
+    next_counts: HashMap<Vec<Hand>, Vec<usize>>,
+    fallback: Frequency,
+}
+
+impl Markov {
+    pub fn new(order: usize) -> Markov {
+        return Markov {
+            order,
+            history: Vec::new(),
+            next_counts: HashMap::new(),
+            fallback: Frequency::default(),
+        };
+    }
+
+    fn context(&self) -> Option<&[Hand]> {
+        if self.history.len() < self.order {
+            return None;
+        }
+        return Some(&self.history[self.history.len() - self.order..]);
+    }
+}
+
+impl Strategy for Markov {
+    fn name(&self) -> String {
+        return format!("markov-{}", self.order);
+    }
+
+    fn choose(&mut self, rules: &Rules) -> Hand {
+        let predicted = self
+            .context()
+            .and_then(|context| self.next_counts.get(context))
+            .and_then(|counts| most_common(counts));
+        return match predicted {
+            Some(predicted) => predicted.get_win_hand(rules),
+            None => self.fallback.choose(rules),
+        };
+    }
+
+    fn observe(&mut self, their_hand: Hand, my_hand: Hand, rules: &Rules) {
+        if let Some(context) = self.context().map(|x| x.to_vec()) {
+            let counts = self
+                .next_counts
+                .entry(context)
+                .or_insert_with(|| vec![0; rules.len()]);
+            counts[their_hand.0] += 1;
+        }
+        self.history.push(their_hand);
+        self.fallback.observe(their_hand, my_hand, rules);
+    }
+}
+
+// Keeps its hand after a win, otherwise switches to what would have beaten the opponent's last hand.
+#[derive(Default)]
+pub struct WinStayLoseShift {
+    next: Option<Hand>,
+}
+
+impl Strategy for WinStayLoseShift {
+    fn name(&self) -> String {
+        return "win-stay-lose-shift".to_string();
+    }
+
+    fn choose(&mut self, _rules: &Rules) -> Hand {
+        return self.next.unwrap_or(Hand(0));
+    }
+
+    fn observe(&mut self, their_hand: Hand, my_hand: Hand, rules: &Rules) {
+        self.next = match my_hand.get_result_against(&their_hand, rules) {
+            GameResult::Win => Some(my_hand),
+            _ => Some(their_hand.get_win_hand(rules)),
+        };
+    }
+}
+
+// Plays uniformly at random.
+pub struct Random {
+    rng: Rng,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        return Random {
+            rng: Rng::new(seed),
+        };
+    }
+}
+
+impl Strategy for Random {
+    fn name(&self) -> String {
+        return "random".to_string();
+    }
+
+    fn choose(&mut self, rules: &Rules) -> Hand {
+        return Hand(self.rng.below(rules.len()));
+    }
+
+    fn observe(&mut self, _their_hand: Hand, _my_hand: Hand, _rules: &Rules) {}
+}
+
+// Plays a fixed list of hands, starting over when it runs out.
+pub struct Scripted {
+    name: String,
+    hands: Vec<Hand>,
+    next: usize,
+}
+
+impl Scripted {
+    pub fn new(name: &str, hands: Vec<Hand>) -> Scripted {
+        return Scripted {
+            name: name.to_string(),
+            hands,
+            next: 0,
+        };
+    }
+}
+
+impl Strategy for Scripted {
+    fn name(&self) -> String {
+        return self.name.clone();
+    }
+
+    fn choose(&mut self, _rules: &Rules) -> Hand {
+        let hand = self.hands[self.next % self.hands.len()];
+        self.next += 1;
+        return hand;
+    }
+
+    fn observe(&mut self, _their_hand: Hand, _my_hand: Hand, _rules: &Rules) {}
+}
+
+// Builds a strategy from its command line name: `frequency`, `markov:<order>`,
+// `win-stay-lose-shift`, `random` or `guide:<path>` (plays the second column of a guide, read as
+// shapes).
+pub fn from_spec(spec: &str, seed: u64, rules: &Rules) -> Result<Box<dyn Strategy>, String> {
+    let (kind, argument) = match spec.split_once(':') {
+        Some((kind, argument)) => (kind, Some(argument)),
+        None => (spec, None),
+    };
+
+    return match (kind, argument) {
+        ("frequency", None) => Ok(Box::new(Frequency::default())),
+        ("markov", order) => {
+            let order = order
+                .unwrap_or("2")
+                .parse::<usize>()
+                .map_err(|_| format!("invalid markov order in {}", spec))?;
+            Ok(Box::new(Markov::new(order)))
+        }
+        ("win-stay-lose-shift", None) => Ok(Box::new(WinStayLoseShift::default())),
+        ("random", None) => Ok(Box::new(Random::new(seed))),
+        ("guide", Some(path)) => {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| format!("cannot read {}: {}", path, e))?;
+            let guide = crate::parse_guide(&content, rules)?;
+            let hands: Vec<Hand> = guide.part1.iter().map(|x| x.my_hand).collect();
+            if hands.is_empty() {
+                return Err(format!("{} is empty", path));
+            }
+            Ok(Box::new(Scripted::new(spec, hands)))
+        }
+        _ => Err(format!("unknown strategy {}", spec)),
+    };
+}
+
+// Plays `strategy` against the opponent's hands, in order.
+pub fn play(strategy: &mut dyn Strategy, elf_hands: &[Hand], rules: &Rules) -> Vec<GameRound> {
+    let mut rounds: Vec<GameRound> = Vec::new();
+    for (round, &elf_hand) in elf_hands.iter().enumerate() {
+        let my_hand = strategy.choose(rules);
+        strategy.observe(elf_hand, my_hand, rules);
+        rounds.push(GameRound {
+            my_hand,
+            elf_hand,
+            round,
+        });
+    }
+    return rounds;
+}
+
+pub fn builtin_strategies(markov_order: usize) -> Vec<Box<dyn Strategy>> {
+    return vec![
+        Box::new(Frequency::default()),
+        Box::new(Markov::new(markov_order)),
+        Box::new(WinStayLoseShift::default()),
+    ];
+}
+
+pub fn evaluate(
+    strategy: &mut dyn Strategy,
+    elf_hands: &[Hand],
+    rules: &Rules,
+    scoring: &Scoring,
+) -> i32 {
+    return total_score(&play(strategy, elf_hands, rules), rules, scoring);
+}
+
+// day2 predict [--order n]
+pub fn run(options: &Options) {
+    let guide = options.guide();
+    let rules = &options.rules;
+    let scoring = &options.scoring;
+    let elf_hands: Vec<Hand> = guide.lines.iter().map(|x| x.elf_hand).collect();
+
+    println!("guide part1 {}", total_score(&guide.part1, rules, scoring));
+    if let Some(rounds) = &guide.part2 {
+        println!("guide part2 {}", total_score(rounds, rules, scoring));
+    }
+
+    let order = options.number::<usize>("--order").unwrap_or(2);
+    for mut strategy in builtin_strategies(order) {
+        let total = evaluate(strategy.as_mut(), &elf_hands, rules, scoring);
+        println!("{} {}", strategy.name(), total);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROCK: Hand = Hand(0);
+    const PAPER: Hand = Hand(1);
+    const SCISSORS: Hand = Hand(2);
+
+    #[test]
+    fn frequency_beats_the_most_played() {
+        let rules = Rules::rock_paper_scissors();
+        let mut frequency = Frequency::default();
+        let rounds = play(&mut frequency, &[ROCK, ROCK, SCISSORS, ROCK, PAPER], &rules);
+        let mine: Vec<Hand> = rounds.iter().map(|x| x.my_hand).collect();
+        // The first shape before anything was seen.
+        assert_eq!(mine, vec![ROCK, PAPER, PAPER, PAPER, PAPER]);
+
+        // Ties go to the first shape of the table.
+        let mut frequency = Frequency::default();
+        play(&mut frequency, &[SCISSORS, PAPER], &rules);
+        assert_eq!(frequency.choose(&rules), SCISSORS);
+    }
+
+    #[test]
+    fn markov_learns_a_cycle() {
+        let rules = Rules::rock_paper_scissors();
+        let cycle: Vec<Hand> = [ROCK, PAPER, SCISSORS].repeat(4);
+        let mut markov = from_spec("markov:1", 0, &rules).unwrap();
+        let rounds = play(markov.as_mut(), &cycle, &rules);
+        let won: Vec<bool> = rounds
+            .iter()
+            .map(|x| x.my_hand.get_result_against(&x.elf_hand, &rules) == GameResult::Win)
+            .collect();
+        // From the second cycle on (the first round of it still through the frequency fallback).
+        assert!(won[3..].iter().all(|x| *x), "{:?}", won);
+        assert_eq!(markov.name(), "markov-1");
+    }
+
+    #[test]
+    fn win_stay_lose_shift() {
+        let rules = Rules::rock_paper_scissors();
+        let mut strategy = WinStayLoseShift::default();
+        assert_eq!(strategy.choose(&rules), ROCK);
+        // Rock beat scissors: stay.
+        strategy.observe(SCISSORS, ROCK, &rules);
+        assert_eq!(strategy.choose(&rules), ROCK);
+        // Rock lost to paper: play scissors, which beats paper.
+        strategy.observe(PAPER, ROCK, &rules);
+        assert_eq!(strategy.choose(&rules), SCISSORS);
+        // A draw shifts too.
+        strategy.observe(SCISSORS, SCISSORS, &rules);
+        assert_eq!(strategy.choose(&rules), ROCK);
+    }
+
+    #[test]
+    fn specs() {
+        let rules = Rules::rock_paper_scissors();
+        for (spec, name) in [
+            ("frequency", "frequency"),
+            ("markov", "markov-2"),
+            ("markov:3", "markov-3"),
+            ("win-stay-lose-shift", "win-stay-lose-shift"),
+            ("random", "random"),
+        ] {
+            assert_eq!(from_spec(spec, 0, &rules).unwrap().name(), name);
+        }
+        for spec in ["markov:x", "frequency:1", "random:2", "guide", "bogus"] {
+            assert!(from_spec(spec, 0, &rules).is_err(), "{}", spec);
+        }
+        assert!(from_spec("guide:does-not-exist.txt", 0, &rules)
+            .err()
+            .unwrap()
+            .starts_with("cannot read does-not-exist.txt"));
+    }
+}
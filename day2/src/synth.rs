@@ -0,0 +1,397 @@
+// Guides written backwards: given the elf's hands and a total, find a second column that scores
+// exactly that total, read as shapes (part 1) or as outcomes (part 2).
+//
+// Every round has a few possible scores (one per shape, or one per outcome). Going from the last
+// round to the first, `reachable[r]` is the set of totals rounds r.. can add up to, kept as a bitset
+// offset by the lowest of them. The guide is then built from the first round on, each round picking
+// at random (from the seed) among the choices that still leave the rest of the target reachable.
+//
+// With a win limit, a bitset isn't enough: `fewest_wins[r]` holds, for every total rounds r.. can
+// add up to, the fewest of them that have to be wins to get there. The guide is built the same way,
+// each round picking among the choices whose wins so far plus the fewest wins left stay within the
+// limit, so a guide is found whenever one exists.
+
+use crate::rng::Rng;
+use crate::rules::{Rules, OPPONENT_SYMBOLS, PLAYER_SYMBOLS};
+use crate::scoring::Scoring;
+use crate::{parse_guide, total_score, GameResult, GameRound, Hand, Options};
+
+// Beyond this many words of bitsets (256 MB), the target table is too big to build.
+const MAX_WORDS: usize = 1 << 25;
+// The same for the win counts of a win limit, 4 bytes per total.
+const MAX_WIN_ENTRIES: usize = 1 << 26;
+// A total no choice of the remaining rounds adds up to.
+const UNREACHABLE: u32 = u32::MAX;
+
+const OUTCOMES: [GameResult; 3] = [GameResult::Lost, GameResult::Draw, GameResult::Win];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Constraints {
+    // The largest fraction of rounds the guide may win.
+    pub max_win_rate: Option<f64>,
+}
+
+// The rounds a guide line could turn into for `part`.
+fn choices(elf_hand: Hand, round: usize, part: u32, rules: &Rules) -> Vec<GameRound> {
+    let my_hands: Vec<Hand> = match part {
+        1 => rules.hands().collect(),
+        _ => OUTCOMES
+            .iter()
+            .map(|x| elf_hand.get_hand_from_game_result(x, rules))
+            .collect(),
+    };
+    return my_hands
+        .into_iter()
+        .map(|my_hand| GameRound {
+            my_hand,
+            elf_hand,
+            round,
+        })
+        .collect();
+}
+
+fn has_bit(bits: &[u64], i: i64) -> bool {
+    if i < 0 || i as usize >= bits.len() * 64 {
+        return false;
+    }
+    let i = i as usize;
+    return bits[i / 64] >> (i % 64) & 1 == 1;
+}
+
+// dst |= src << shift, dst being wide enough.
+fn or_shifted(dst: &mut [u64], src: &[u64], shift: usize) {
+    let words = shift / 64;
+    let bits = shift % 64;
+    for (i, &word) in src.iter().enumerate() {
+        if word == 0 {
+            continue;
+        }
+        let j = i + words;
+        if j < dst.len() {
+            dst[j] |= word << bits;
+        }
+        if bits > 0 && j + 1 < dst.len() {
+            dst[j + 1] |= word >> (64 - bits);
+        }
+    }
+}
+
+// A guide whose rounds against `elf_hands` add up to `target`, or why there is none.
+pub fn synthesize(
+    elf_hands: &[Hand],
+    part: u32,
+    target: i32,
+    constraints: &Constraints,
+    seed: u64,
+    rules: &Rules,
+    scoring: &Scoring,
+) -> Result<Vec<GameRound>, String> {
+    let n = elf_hands.len();
+    let options: Vec<Vec<(GameRound, i64)>> = elf_hands
+        .iter()
+        .enumerate()
+        .map(|(round, &elf_hand)| {
+            choices(elf_hand, round, part, rules)
+                .into_iter()
+                .map(|x| (x, x.get_game_result(rules, scoring) as i64))
+                .collect()
+        })
+        .collect();
+
+    // lowest[r] and highest[r] bound the totals of rounds r..
+    let mut lowest: Vec<i64> = vec![0; n + 1];
+    let mut highest: Vec<i64> = vec![0; n + 1];
+    for r in (0..n).rev() {
+        lowest[r] = lowest[r + 1] + options[r].iter().map(|x| x.1).min().unwrap();
+        highest[r] = highest[r + 1] + options[r].iter().map(|x| x.1).max().unwrap();
+    }
+    let target = target as i64;
+    if target < lowest[0] || target > highest[0] {
+        return Err(format!(
+            "{} is out of range, these rounds score between {} and {}",
+            target, lowest[0], highest[0]
+        ));
+    }
+    let words: usize = (0..=n)
+        .map(|r| ((highest[r] - lowest[r]) as usize) / 64 + 1)
+        .sum();
+    if words > MAX_WORDS {
+        return Err(format!("{} rounds are too many to search", n));
+    }
+
+    let mut reachable: Vec<Vec<u64>> = vec![Vec::new(); n + 1];
+    reachable[n] = vec![1];
+    for r in (0..n).rev() {
+        let low = lowest[r] - lowest[r + 1];
+        let mut bits = vec![0; ((highest[r] - lowest[r]) as usize) / 64 + 1];
+        for &(_, score) in &options[r] {
+            or_shifted(&mut bits, &reachable[r + 1], (score - low) as usize);
+        }
+        reachable[r] = bits;
+    }
+    if !has_bit(&reachable[0], target - lowest[0]) {
+        return Err(format!("no guide scores exactly {}", target));
+    }
+
+    let wins_round =
+        |x: &GameRound| x.my_hand.get_result_against(&x.elf_hand, rules) == GameResult::Win;
+    let mut rng = Rng::new(seed);
+    let mut rounds: Vec<GameRound> = Vec::new();
+    let mut remaining = target;
+
+    let Some(rate) = constraints.max_win_rate else {
+        for r in 0..n {
+            let feasible: Vec<&(GameRound, i64)> = options[r]
+                .iter()
+                .filter(|(_, score)| has_bit(&reachable[r + 1], remaining - score - lowest[r + 1]))
+                .collect();
+            let &(round, score) = feasible[rng.below(feasible.len())];
+            remaining -= score;
+            rounds.push(round);
+        }
+        return Ok(rounds);
+    };
+
+    let max_wins = (rate * n as f64 + 1e-9).floor() as u32;
+    let entries: usize = (0..=n).map(|r| (highest[r] - lowest[r]) as usize + 1).sum();
+    if entries > MAX_WIN_ENTRIES {
+        return Err(format!(
+            "{} rounds are too many to search with a win limit",
+            n
+        ));
+    }
+
+    // fewest_wins[r][s - lowest[r]]: the fewest wins among rounds r.. adding up to s.
+    let mut fewest_wins: Vec<Vec<u32>> = vec![Vec::new(); n + 1];
+    fewest_wins[n] = vec![0];
+    for r in (0..n).rev() {
+        let mut wins = vec![UNREACHABLE; (highest[r] - lowest[r]) as usize + 1];
+        for &(round, score) in &options[r] {
+            let won = wins_round(&round) as u32;
+            let offset = (score + lowest[r + 1] - lowest[r]) as usize;
+            for (i, &after) in fewest_wins[r + 1].iter().enumerate() {
+                if after != UNREACHABLE {
+                    wins[offset + i] = wins[offset + i].min(after + won);
+                }
+            }
+        }
+        fewest_wins[r] = wins;
+    }
+
+    let fewest = fewest_wins[0][(target - lowest[0]) as usize];
+    if fewest > max_wins {
+        return Err(format!(
+            "no guide scores {} with at most {} wins, the fewest is {}",
+            target, max_wins, fewest
+        ));
+    }
+
+    let mut wins = 0;
+    for r in 0..n {
+        let within_limit: Vec<&(GameRound, i64)> = options[r]
+            .iter()
+            .filter(|(round, score)| {
+                let rest = remaining - score - lowest[r + 1];
+                if rest < 0 || rest as usize >= fewest_wins[r + 1].len() {
+                    return false;
+                }
+                let after = fewest_wins[r + 1][rest as usize];
+                after != UNREACHABLE && wins + wins_round(round) as u32 + after <= max_wins
+            })
+            .collect();
+        let &(round, score) = within_limit[rng.below(within_limit.len())];
+        wins += wins_round(&round) as u32;
+        remaining -= score;
+        rounds.push(round);
+    }
+    return Ok(rounds);
+}
+
+// The guide lines of `rounds`, the second column written the way `part` reads it.
+pub fn to_guide(rounds: &[GameRound], part: u32, rules: &Rules) -> String {
+    let letter = |symbols: &str, hand: Hand| symbols.chars().nth(hand.0).unwrap();
+    let lines: Vec<String> = rounds
+        .iter()
+        .map(|x| {
+            let response = match (part, x.my_hand.get_result_against(&x.elf_hand, rules)) {
+                (1, _) => letter(PLAYER_SYMBOLS, x.my_hand),
+                (_, GameResult::Lost) => 'X',
+                (_, GameResult::Draw) => 'Y',
+                (_, GameResult::Win) => 'Z',
+            };
+            format!("{} {}", letter(OPPONENT_SYMBOLS, x.elf_hand), response)
+        })
+        .collect();
+    return lines.join("\n");
+}
+
+// day2 synth --target n [--part 1|2] [--max-win-rate r] [--seed s] [--length n | input]
+//
+// Prints the guide on stdout, what it scores on stderr.
+pub fn run(options: &Options) {
+    let rules = &options.rules;
+    let scoring = &options.scoring;
+    let target = crate::exit_on_error(
+        options
+            .number::<i32>("--target")
+            .ok_or("synth needs --target".to_string()),
+    );
+    let part = options.number::<u32>("--part").unwrap_or(1);
+    if part != 1 && part != 2 {
+        crate::exit_on_error::<()>(Err("--part must be 1 or 2".to_string()));
+    }
+    let seed = options.number::<u64>("--seed").unwrap_or(0);
+    let constraints = Constraints {
+        max_win_rate: options.number::<f64>("--max-win-rate"),
+    };
+
+    // A generated opponent column uses its own generator so the guide's choices don't shift it.
+    let elf_hands: Vec<Hand> = match options.number::<usize>("--length") {
+        Some(length) => {
+            let mut rng = Rng::new(seed ^ 0x5eed);
+            (0..length).map(|_| Hand(rng.below(rules.len()))).collect()
+        }
+        None => options.guide().lines.iter().map(|x| x.elf_hand).collect(),
+    };
+
+    let rounds = crate::exit_on_error(synthesize(
+        &elf_hands,
+        part,
+        target,
+        &constraints,
+        seed,
+        rules,
+        scoring,
+    ));
+    let text = to_guide(&rounds, part, rules);
+
+    // Read the guide back the way the other modes do, it must score the target.
+    let guide = parse_guide(&text, rules).unwrap();
+    let decoded = match part {
+        1 => guide.part1,
+        _ => guide.part2.unwrap(),
+    };
+    let total = total_score(&decoded, rules, scoring);
+    assert_eq!(total, target, "the synthesized guide scores {}", total);
+
+    let wins = decoded
+        .iter()
+        .filter(|x| x.my_hand.get_result_against(&x.elf_hand, rules) == GameResult::Win)
+        .count();
+    println!("{}", text);
+    eprintln!(
+        "part{} {} in {} rounds, {} wins",
+        part,
+        total,
+        decoded.len(),
+        wins
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wins(rounds: &[GameRound], rules: &Rules) -> usize {
+        return rounds
+            .iter()
+            .filter(|x| x.my_hand.get_result_against(&x.elf_hand, rules) == GameResult::Win)
+            .count();
+    }
+
+    // Every total the rounds against `elf_hands` can score, with the fewest wins that get it.
+    fn brute_force(
+        elf_hands: &[Hand],
+        part: u32,
+        rules: &Rules,
+        scoring: &Scoring,
+    ) -> Vec<(i32, usize)> {
+        let mut best: Vec<(i32, usize)> = Vec::new();
+        let options: Vec<Vec<GameRound>> = elf_hands
+            .iter()
+            .enumerate()
+            .map(|(round, &hand)| choices(hand, round, part, rules))
+            .collect();
+        for mut pick in 0..3usize.pow(elf_hands.len() as u32) {
+            let mut rounds: Vec<GameRound> = Vec::new();
+            for option in &options {
+                rounds.push(option[pick % 3]);
+                pick /= 3;
+            }
+            let total = total_score(&rounds, rules, scoring);
+            let wins = wins(&rounds, rules);
+            match best.iter_mut().find(|x| x.0 == total) {
+                Some(x) => x.1 = x.1.min(wins),
+                None => best.push((total, wins)),
+            }
+        }
+        return best;
+    }
+
+    #[test]
+    fn reaches_every_target() {
+        let rules = Rules::rock_paper_scissors();
+        let scoring = Scoring::puzzle(&rules);
+        let elf_hands = [Hand(0), Hand(1), Hand(2), Hand(2)];
+        let none = Constraints { max_win_rate: None };
+        for part in [1, 2] {
+            let totals = brute_force(&elf_hands, part, &rules, &scoring);
+            for target in 0..=40 {
+                let result = synthesize(&elf_hands, part, target, &none, 7, &rules, &scoring);
+                if totals.iter().any(|x| x.0 == target) {
+                    assert_eq!(total_score(&result.unwrap(), &rules, &scoring), target);
+                } else {
+                    assert!(result.is_err(), "part{} target {}", part, target);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn win_limit_is_exact() {
+        let rules = Rules::rock_paper_scissors();
+        let scoring = Scoring::puzzle(&rules);
+        let elf_hands = [Hand(0), Hand(1), Hand(0), Hand(2)];
+        let n = elf_hands.len() as f64;
+        for part in [1, 2] {
+            for (target, fewest) in brute_force(&elf_hands, part, &rules, &scoring) {
+                let limit = Constraints {
+                    max_win_rate: Some(fewest as f64 / n),
+                };
+                for seed in 0..5 {
+                    let rounds =
+                        synthesize(&elf_hands, part, target, &limit, seed, &rules, &scoring)
+                            .unwrap();
+                    assert_eq!(total_score(&rounds, &rules, &scoring), target);
+                    assert!(wins(&rounds, &rules) <= fewest);
+                }
+                if fewest > 0 {
+                    let tighter = Constraints {
+                        max_win_rate: Some((fewest - 1) as f64 / n),
+                    };
+                    assert!(
+                        synthesize(&elf_hands, part, target, &tighter, 0, &rules, &scoring)
+                            .is_err()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn win_limit_looks_past_the_next_round() {
+        // A A C scores 14 without a win (three draws), but losing the first round still leaves 14
+        // reachable, only through a win: avoiding wins round by round could end up there.
+        let rules = Rules::rock_paper_scissors();
+        let scoring = Scoring::puzzle(&rules);
+        let elf_hands = [Hand(0), Hand(0), Hand(2)];
+        let no_wins = Constraints {
+            max_win_rate: Some(0.0),
+        };
+        for seed in 0..20 {
+            let rounds = synthesize(&elf_hands, 1, 14, &no_wins, seed, &rules, &scoring).unwrap();
+            assert_eq!(total_score(&rounds, &rules, &scoring), 14);
+            assert_eq!(wins(&rounds, &rules), 0);
+        }
+    }
+}
@@ -0,0 +1,237 @@
+// A round robin tournament: every player plays every other player for the same number of rounds,
+// each match starting from fresh strategies.
+//
+// Every random choice comes from the tournament seed (each match gets its own seed derived from
+// it), so a tournament can be replayed exactly.
+
+use crate::rules::Rules;
+use crate::scoring::Scoring;
+use crate::strategy::{from_spec, Strategy};
+use crate::{GameRound, Options};
+
+#[derive(Debug, Clone)]
+pub struct Standing {
+    pub player: usize,
+    pub score: i64,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    pub players: Vec<String>,
+    // head_to_head[i][j] is what player i scored against player j.
+    pub head_to_head: Vec<Vec<i64>>,
+    // Best first: most points, then most match wins.
+    pub leaderboard: Vec<Standing>,
+}
+
+fn match_seed(seed: u64, i: usize, j: usize) -> u64 {
+    return seed ^ ((i as u64) << 32 | j as u64).wrapping_mul(0x9e3779b97f4a7c15);
+}
+
+// Both players' scores after `rounds` rounds.
+fn play_match(
+    first: &mut dyn Strategy,
+    second: &mut dyn Strategy,
+    rounds: usize,
+    rules: &Rules,
+    scoring: &Scoring,
+) -> (i64, i64) {
+    let mut scores = (0, 0);
+    for round in 0..rounds {
+        let first_hand = first.choose(rules);
+        let second_hand = second.choose(rules);
+        first.observe(second_hand, first_hand, rules);
+        second.observe(first_hand, second_hand, rules);
+
+        let first_round = GameRound {
+            my_hand: first_hand,
+            elf_hand: second_hand,
+            round,
+        };
+        let second_round = GameRound {
+            my_hand: second_hand,
+            elf_hand: first_hand,
+            round,
+        };
+        scores.0 += first_round.get_game_result(rules, scoring) as i64;
+        scores.1 += second_round.get_game_result(rules, scoring) as i64;
+    }
+    return scores;
+}
+
+pub fn round_robin(
+    specs: &[String],
+    rounds: usize,
+    seed: u64,
+    rules: &Rules,
+    scoring: &Scoring,
+) -> Result<Tournament, String> {
+    let n = specs.len();
+    let mut head_to_head = vec![vec![0; n]; n];
+    let mut leaderboard: Vec<Standing> = (0..n)
+        .map(|player| Standing {
+            player,
+            score: 0,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+        })
+        .collect();
+
+    for i in 0..n {
+        for j in i + 1..n {
+            let match_seed = match_seed(seed, i, j);
+            let mut first = from_spec(&specs[i], match_seed, rules)?;
+            let mut second = from_spec(&specs[j], match_seed.rotate_left(17), rules)?;
+            let (first_score, second_score) =
+                play_match(first.as_mut(), second.as_mut(), rounds, rules, scoring);
+
+            head_to_head[i][j] = first_score;
+            head_to_head[j][i] = second_score;
+            leaderboard[i].score += first_score;
+            leaderboard[j].score += second_score;
+            if first_score > second_score {
+                leaderboard[i].wins += 1;
+                leaderboard[j].losses += 1;
+            } else if first_score < second_score {
+                leaderboard[i].losses += 1;
+                leaderboard[j].wins += 1;
+            } else {
+                leaderboard[i].draws += 1;
+                leaderboard[j].draws += 1;
+            }
+        }
+    }
+
+    leaderboard.sort_by(|a, b| b.score.cmp(&a.score).then(b.wins.cmp(&a.wins)));
+    return Ok(Tournament {
+        players: specs.to_vec(),
+        head_to_head,
+        leaderboard,
+    });
+}
+
+// day2 tournament --players <spec,spec,...> [--rounds n] [--seed n]
+pub fn run(options: &Options) {
+    let specs: Vec<String> = options
+        .flag("--players")
+        .unwrap_or("frequency,markov:1,markov:2,win-stay-lose-shift,random")
+        .split(',')
+        .map(|x| x.trim().to_string())
+        .collect();
+    let rounds = options.number::<usize>("--rounds").unwrap_or(1000);
+    let seed = options.number::<u64>("--seed").unwrap_or(0);
+
+    let tournament = round_robin(&specs, rounds, seed, &options.rules, &options.scoring)
+        .unwrap_or_else(|e| panic!("{}", e));
+
+    println!("seed {} rounds {}", seed, rounds);
+    for (rank, standing) in tournament.leaderboard.iter().enumerate() {
+        println!(
+            "{}. {} {} (won {} drew {} lost {})",
+            rank + 1,
+            tournament.players[standing.player],
+            standing.score,
+            standing.wins,
+            standing.draws,
+            standing.losses
+        );
+    }
+
+    println!();
+    println!("head to head (row's score against column):");
+    for (i, scores) in tournament.head_to_head.iter().enumerate() {
+        let scores: Vec<String> = scores
+            .iter()
+            .enumerate()
+            .map(|(j, score)| {
+                if i == j {
+                    "-".to_string()
+                } else {
+                    score.to_string()
+                }
+            })
+            .collect();
+        println!("{} {}", tournament.players[i], scores.join(" "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specs(names: &[&str]) -> Vec<String> {
+        return names.iter().map(|x| x.to_string()).collect();
+    }
+
+    fn standings(tournament: &Tournament) -> Vec<(usize, i64, usize, usize, usize)> {
+        return tournament
+            .leaderboard
+            .iter()
+            .map(|x| (x.player, x.score, x.wins, x.draws, x.losses))
+            .collect();
+    }
+
+    #[test]
+    fn same_seed_same_tournament() {
+        let rules = Rules::rock_paper_scissors();
+        let scoring = Scoring::puzzle(&rules);
+        let players = specs(&[
+            "random",
+            "frequency",
+            "markov:1",
+            "win-stay-lose-shift",
+            "random",
+        ]);
+
+        let first = round_robin(&players, 200, 7, &rules, &scoring).unwrap();
+        let second = round_robin(&players, 200, 7, &rules, &scoring).unwrap();
+        assert_eq!(first.head_to_head, second.head_to_head);
+        assert_eq!(standings(&first), standings(&second));
+
+        // The two random players get seeds of their own in each match.
+        assert_ne!(first.head_to_head[0], first.head_to_head[4]);
+        let other = round_robin(&players, 200, 8, &rules, &scoring).unwrap();
+        assert_ne!(first.head_to_head[0], other.head_to_head[0]);
+    }
+
+    #[test]
+    fn head_to_head_is_each_match() {
+        let rules = Rules::rock_paper_scissors();
+        let scoring = Scoring::puzzle(&rules);
+        let players = specs(&["random", "frequency", "win-stay-lose-shift"]);
+        let tournament = round_robin(&players, 100, 3, &rules, &scoring).unwrap();
+
+        for i in 0..players.len() {
+            assert_eq!(tournament.head_to_head[i][i], 0);
+            for j in i + 1..players.len() {
+                let seed = match_seed(3, i, j);
+                let mut first = from_spec(&players[i], seed, &rules).unwrap();
+                let mut second = from_spec(&players[j], seed.rotate_left(17), &rules).unwrap();
+                let scores = play_match(first.as_mut(), second.as_mut(), 100, &rules, &scoring);
+                assert_eq!(
+                    (tournament.head_to_head[i][j], tournament.head_to_head[j][i]),
+                    scores
+                );
+            }
+        }
+
+        // The leaderboard adds up each player's row.
+        for standing in &tournament.leaderboard {
+            let row: i64 = tournament.head_to_head[standing.player].iter().sum();
+            assert_eq!(standing.score, row);
+            assert_eq!(standing.wins + standing.draws + standing.losses, 2);
+        }
+    }
+
+    #[test]
+    fn unknown_player() {
+        let rules = Rules::rock_paper_scissors();
+        let scoring = Scoring::puzzle(&rules);
+        let result = round_robin(&specs(&["random", "bogus"]), 10, 0, &rules, &scoring);
+        assert_eq!(result.err(), Some("unknown strategy bogus".to_string()));
+    }
+}
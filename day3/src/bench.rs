@@ -0,0 +1,116 @@
+// Times both parts with `ItemSet` against the first version of this day, which looked for shared
+// items with `str::contains` and for priorities with a linear search through the alphabet.
+//
+// The timings only mean something in a release build. Even there the gap is modest (around 1.5x on
+// the puzzle input, and it depends on the machine), and a debug build can make the bitset the
+// slower of the two.
+
+use std::time::{Duration, Instant};
+
+use crate::priorities::PriorityTable;
+
+// Computes the answer of a part from the whole input.
+type Part = fn(&str, &PriorityTable) -> Result<i32, String>;
+
+static ALPHABET_VEC: [char; 52] = [
+    'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's',
+    't', 'u', 'v', 'w', 'x', 'y', 'z', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L',
+    'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
+];
+
+fn contains_priority(letter: char) -> i32 {
+    let index = ALPHABET_VEC.iter().position(|&r| r == letter).unwrap();
+    return index as i32 + 1;
+}
+
+fn contains_part1(input: &str, _table: &PriorityTable) -> Result<i32, String> {
+    let mut result = 0;
+    for line in input.lines() {
+        let (left, right) = line.split_at(line.len() / 2);
+        let shared = left.chars().find(|c| right.contains(*c)).unwrap();
+        result += contains_priority(shared);
+    }
+    return Ok(result);
+}
+
+fn contains_part2(input: &str, _table: &PriorityTable) -> Result<i32, String> {
+    let lines: Vec<&str> = input.lines().collect();
+    let mut result = 0;
+    for chunk in lines.chunks(3) {
+        let shared = chunk[0]
+            .chars()
+            .find(|c| chunk[1].contains(*c) && chunk[2].contains(*c))
+            .unwrap();
+        result += contains_priority(shared);
+    }
+    return Ok(result);
+}
+
+// Runs `f` `repeat` times, returns its answer and the total time.
+fn time(repeat: usize, input: &str, table: &PriorityTable, f: Part) -> (i32, Duration) {
+    let start = Instant::now();
+    let mut answer = Ok(0);
+    for _ in 0..repeat {
+        answer = std::hint::black_box(f(std::hint::black_box(input), table));
+    }
+    let elapsed = start.elapsed();
+    return (crate::exit_on_error(answer), elapsed);
+}
+
+// day3 bench [--repeat n] [input], built with --release
+//
+// Exits with 1 if the two versions disagree.
+pub fn run(args: &[String]) {
+    let mut path = "input.txt";
+    let mut repeat = 1000;
+
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--repeat" => {
+                repeat = args_iter
+                    .next()
+                    .and_then(|x| x.parse().ok())
+                    .expect("--repeat needs a number")
+            }
+            other => path = other,
+        }
+    }
+    let input = crate::read_input_file(path);
+    let table = PriorityTable::puzzle();
+    if cfg!(debug_assertions) {
+        println!("debug build: the timings below say little, run with --release");
+    }
+
+    let mut failed = false;
+    let parts: [(&str, Part, Part); 2] = [
+        ("part1", contains_part1, crate::sum_part1),
+        ("part2", contains_part2, |x, table| {
+            crate::sum_part2(x, 3, table)
+        }),
+    ];
+    // The first version panics on a character that isn't a letter, stop before it runs.
+    for (_, _, item_set) in parts {
+        crate::exit_on_error(item_set(&input, &table));
+    }
+    for (name, contains, item_set) in parts {
+        let (expected, contains_time) = time(repeat, &input, &table, contains);
+        let (answer, item_set_time) = time(repeat, &input, &table, item_set);
+        println!(
+            "{} {}: contains {:.2?}, item set {:.2?} ({:.1}x)",
+            name,
+            answer,
+            contains_time,
+            item_set_time,
+            contains_time.as_secs_f64() / item_set_time.as_secs_f64().max(1e-9)
+        );
+        if answer != expected {
+            println!("mismatch: contains gives {}", expected);
+            failed = true;
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
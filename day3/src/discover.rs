@@ -0,0 +1,279 @@
+// Finds the groups when the list isn't in group order: a partition of the rucksacks into groups of
+// k where the rucksacks of each group share exactly one item type, their badge.
+//
+// The search is a backtracking over the rucksacks. It starts each group from the rucksack with the
+// fewest possible partners left, and adds the others in list order, keeping the items the group
+// still shares: an empty intersection ends that branch right away. Two rucksacks can only be in the
+// same group when they share an item, which is precomputed as a bitset of partners per rucksack
+// (an `ItemSet` over rucksack indices); a rucksack left with fewer than k - 1 partners ends the
+// branch too.
+//
+// The search stops at the second partition found, which is enough to tell whether the first one
+// is unique, or after `limit` steps, in which case uniqueness is unknown.
+
+use crate::item_set::ItemSet;
+use crate::Options;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Uniqueness {
+    Unique,
+    Several,
+    // The search ran out of steps before proving either.
+    Unknown,
+}
+
+#[derive(Debug, Clone)]
+pub struct Discovery {
+    // Each group lists its rucksacks by index in the list, in list order.
+    pub groups: Vec<Vec<usize>>,
+    // Table index of each group's badge.
+    pub badges: Vec<usize>,
+    pub uniqueness: Uniqueness,
+    pub steps: usize,
+}
+
+struct Search<'a> {
+    sets: &'a [ItemSet],
+    group_size: usize,
+    partners: Vec<ItemSet>,
+    unassigned: ItemSet,
+    current: Vec<Vec<usize>>,
+    solutions: Vec<Vec<Vec<usize>>>,
+    steps: usize,
+    limit: usize,
+}
+
+impl Search<'_> {
+    fn stopped(&self) -> bool {
+        return self.solutions.len() >= 2 || self.steps >= self.limit;
+    }
+
+    fn search(&mut self) {
+        if self.stopped() {
+            return;
+        }
+        self.steps += 1;
+        if self.unassigned.is_empty() {
+            self.solutions.push(self.current.clone());
+            return;
+        }
+
+        let mut anchor: Option<(usize, usize)> = None;
+        for i in self.unassigned.iter() {
+            let count = self.partners[i].intersection(&self.unassigned).len();
+            if count < self.group_size - 1 {
+                return;
+            }
+            if anchor.is_none_or(|x| count < x.1) {
+                anchor = Some((i, count));
+            }
+        }
+        let anchor = anchor.unwrap().0;
+
+        self.unassigned.remove(anchor);
+        let candidates: Vec<usize> = self.partners[anchor]
+            .intersection(&self.unassigned)
+            .iter()
+            .collect();
+        let shared = self.sets[anchor].clone();
+        self.extend(&mut vec![anchor], &shared, &candidates, 0);
+        self.unassigned.insert(anchor);
+    }
+
+    // Adds candidates[from..] to `group`, whose rucksacks all carry `shared`.
+    fn extend(
+        &mut self,
+        group: &mut Vec<usize>,
+        shared: &ItemSet,
+        candidates: &[usize],
+        from: usize,
+    ) {
+        if group.len() == self.group_size {
+            if shared.len() != 1 {
+                return;
+            }
+            for &x in group.iter() {
+                self.unassigned.remove(x);
+            }
+            let mut sorted = group.clone();
+            sorted.sort();
+            self.current.push(sorted);
+            self.search();
+            self.current.pop();
+            for &x in &group[1..] {
+                self.unassigned.insert(x);
+            }
+            return;
+        }
+
+        for (i, &candidate) in candidates.iter().enumerate().skip(from) {
+            if self.stopped() {
+                return;
+            }
+            let still_shared = shared.intersection(&self.sets[candidate]);
+            if still_shared.is_empty() {
+                continue;
+            }
+            group.push(candidate);
+            self.extend(group, &still_shared, candidates, i + 1);
+            group.pop();
+        }
+    }
+}
+
+pub fn discover(sets: &[ItemSet], group_size: usize, limit: usize) -> Result<Discovery, String> {
+    if !sets.len().is_multiple_of(group_size) {
+        return Err(format!(
+            "{} rucksacks don't make groups of {}",
+            sets.len(),
+            group_size
+        ));
+    }
+
+    let partners: Vec<ItemSet> = sets
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            sets.iter()
+                .enumerate()
+                .filter(|(j, b)| *j != i && !a.intersection(b).is_empty())
+                .map(|(j, _)| j)
+                .collect()
+        })
+        .collect();
+    let mut search = Search {
+        sets,
+        group_size,
+        partners,
+        unassigned: (0..sets.len()).collect(),
+        current: Vec::new(),
+        solutions: Vec::new(),
+        steps: 0,
+        limit,
+    };
+    search.search();
+
+    let uniqueness = match search.solutions.len() {
+        0 if search.steps >= limit => {
+            return Err(format!("no partition found in {} steps", limit));
+        }
+        0 => return Err("no partition where every group has a single badge".to_string()),
+        1 if search.steps >= limit => Uniqueness::Unknown,
+        1 => Uniqueness::Unique,
+        _ => Uniqueness::Several,
+    };
+
+    let mut groups = search.solutions.swap_remove(0);
+    groups.sort();
+    let badges: Vec<usize> = groups
+        .iter()
+        .map(|group| {
+            let shared = group
+                .iter()
+                .map(|x| sets[*x].clone())
+                .reduce(|a, b| a.intersection(&b))
+                .unwrap();
+            shared.first().unwrap()
+        })
+        .collect();
+
+    return Ok(Discovery {
+        groups,
+        badges,
+        uniqueness,
+        steps: search.steps,
+    });
+}
+
+// day3 discover [--group-size k] [--limit steps] [--priorities <file>] [input]
+pub fn run(options: &Options) {
+    let input = crate::read_input_file(&options.path);
+    let table = &options.table;
+    let sets: Vec<ItemSet> = crate::exit_on_error(
+        input
+            .lines()
+            .enumerate()
+            .map(|(i, x)| crate::get_rucksack_set(x, i, table))
+            .collect(),
+    );
+    let limit = options.number::<usize>("--limit").unwrap_or(1_000_000);
+
+    let discovery = discover(&sets, options.group_size, limit).unwrap_or_else(|e| {
+        println!("{}", e);
+        std::process::exit(1);
+    });
+    let mut total = 0;
+    for (group, &badge) in discovery.groups.iter().zip(&discovery.badges) {
+        let lines: Vec<String> = group.iter().map(|x| (x + 1).to_string()).collect();
+        println!("lines {}: badge {}", lines.join(", "), table.item(badge));
+        total += table.priority_at(badge);
+    }
+    println!("{}", total);
+
+    match discovery.uniqueness {
+        Uniqueness::Unique => println!("the only partition"),
+        Uniqueness::Several => println!("not unique: another partition works too"),
+        Uniqueness::Unknown => println!(
+            "stopped after {} steps without finding another partition, uniqueness unknown",
+            discovery.steps
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::priorities::PriorityTable;
+
+    const EXAMPLE: &str = include_str!("../input-test.txt");
+
+    // Every rucksack is in exactly one group, and each group shares its badge and nothing else.
+    fn assert_valid(discovery: &Discovery, sets: &[ItemSet], group_size: usize) {
+        let mut seen: Vec<usize> = discovery.groups.iter().flatten().cloned().collect();
+        seen.sort();
+        assert_eq!(seen, (0..sets.len()).collect::<Vec<_>>());
+        for (group, &badge) in discovery.groups.iter().zip(&discovery.badges) {
+            assert_eq!(group.len(), group_size);
+            let shared = group
+                .iter()
+                .map(|x| sets[*x].clone())
+                .reduce(|a, b| a.intersection(&b))
+                .unwrap();
+            assert_eq!(shared.iter().collect::<Vec<_>>(), vec![badge]);
+        }
+    }
+
+    #[test]
+    fn finds_groups_in_a_shuffled_example() {
+        let table = PriorityTable::puzzle();
+        let lines: Vec<&str> = EXAMPLE.lines().collect();
+        let shuffled: Vec<&str> = [4, 0, 2, 5, 1, 3].iter().map(|x| lines[*x]).collect();
+        let sets: Vec<ItemSet> = shuffled.iter().map(|x| table.set(x).unwrap()).collect();
+
+        let discovery = discover(&sets, 3, 1_000_000).unwrap();
+        assert_valid(&discovery, &sets, 3);
+        // Besides the puzzle's groups (badges r and Z, 70), lines 1, 2, 4 and 3, 5, 6 of the
+        // example share only J and q, so the partition isn't unique.
+        assert_eq!(discovery.uniqueness, Uniqueness::Several);
+
+        let sets: Vec<ItemSet> = lines.iter().map(|x| table.set(x).unwrap()).collect();
+        let discovery = discover(&sets, 3, 1_000_000).unwrap();
+        assert_valid(&discovery, &sets, 3);
+        let total: i32 = discovery.badges.iter().map(|x| table.priority_at(*x)).sum();
+        assert_eq!(total, 70);
+    }
+
+    #[test]
+    fn reports_no_partition() {
+        let table = PriorityTable::puzzle();
+        let sets: Vec<ItemSet> = ["xyab", "xycd", "efgh", "eijk"]
+            .iter()
+            .map(|x| table.set(x).unwrap())
+            .collect();
+        // The first two share x and y, the last two only e: no pair of pairs works.
+        assert!(discover(&sets, 2, 1_000_000).is_err());
+        // The third shares nothing with the first two.
+        assert!(discover(&sets[..3], 3, 1_000_000).is_err());
+        assert!(discover(&sets, 3, 1_000_000).is_err());
+    }
+}
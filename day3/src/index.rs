@@ -0,0 +1,170 @@
+// Where each item type is: for every type of the priority table, the rucksacks carrying it with the
+// number of copies in each compartment.
+
+use crate::priorities::PriorityTable;
+use crate::{get_rucksack_compartment, Options};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Occurrence {
+    // Index of the rucksack in the list, starting at 0.
+    pub line: usize,
+    pub left: usize,
+    pub right: usize,
+}
+
+pub struct ItemIndex {
+    // The occurrences of each item type, by table index, in list order.
+    occurrences: Vec<Vec<Occurrence>>,
+}
+
+impl ItemIndex {
+    // Fails on the first character that isn't an item type.
+    pub fn build(input: &str, table: &PriorityTable) -> Result<ItemIndex, String> {
+        let mut occurrences: Vec<Vec<Occurrence>> = vec![Vec::new(); table.len()];
+        for (line, rucksack) in input.lines().enumerate() {
+            let (left, right) = get_rucksack_compartment(rucksack);
+            let index_of = |c: char| {
+                table
+                    .index(c)
+                    .ok_or_else(|| format!("line {}: {:?} is not an item type", line + 1, c))
+            };
+            let mut counts: Vec<(usize, usize)> = vec![(0, 0); table.len()];
+            for c in left.chars() {
+                counts[index_of(c)?].0 += 1;
+            }
+            for c in right.chars() {
+                counts[index_of(c)?].1 += 1;
+            }
+            for (index, &(left, right)) in counts.iter().enumerate() {
+                if left + right > 0 {
+                    occurrences[index].push(Occurrence { line, left, right });
+                }
+            }
+        }
+        return Ok(ItemIndex { occurrences });
+    }
+
+    pub fn occurrences(&self, index: usize) -> &[Occurrence] {
+        return &self.occurrences[index];
+    }
+
+    // How many rucksacks carry the item type.
+    pub fn rucksacks(&self, index: usize) -> usize {
+        return self.occurrences[index].len();
+    }
+
+    // How many copies of the item type the whole list holds.
+    pub fn copies(&self, index: usize) -> usize {
+        return self.occurrences[index]
+            .iter()
+            .map(|x| x.left + x.right)
+            .sum();
+    }
+}
+
+fn query(index: &ItemIndex, item: char, table: &PriorityTable) {
+    let Some(i) = table.index(item) else {
+        println!("{:?} is not an item type", item);
+        return;
+    };
+    for occurrence in index.occurrences(i) {
+        let place = match (occurrence.left, occurrence.right) {
+            (left, 0) => format!("left x{}", left),
+            (0, right) => format!("right x{}", right),
+            (left, right) => format!("left x{}, right x{}", left, right),
+        };
+        println!("line {}: {}", occurrence.line + 1, place);
+    }
+    println!(
+        "{}: {} copies in {} rucksacks, priority {} each, {} in total",
+        item,
+        index.copies(i),
+        index.rucksacks(i),
+        table.priority_at(i),
+        index.copies(i) as i64 * table.priority_at(i) as i64
+    );
+}
+
+// day3 index [--item <items>] [--priorities <file>] [input]
+//
+// --item lists where each of the given item types is. Without it, the copies, rucksacks and
+// priority total of every item type in the list.
+pub fn run(options: &Options) {
+    let input = crate::read_input_file(&options.path);
+    let table = &options.table;
+    let index = crate::exit_on_error(ItemIndex::build(&input, table));
+
+    if let Some(items) = options.flag("--item") {
+        for item in items.chars() {
+            query(&index, item, table);
+        }
+        return;
+    }
+
+    let mut copies = 0;
+    let mut total = 0;
+    println!(
+        "{:>4} {:>8} {:>7} {:>9} {:>8}",
+        "item", "priority", "copies", "rucksacks", "total"
+    );
+    for i in 0..table.len() {
+        if index.rucksacks(i) == 0 {
+            continue;
+        }
+        let item_total = index.copies(i) as i64 * table.priority_at(i) as i64;
+        println!(
+            "{:>4} {:>8} {:>7} {:>9} {:>8}",
+            table.item(i),
+            table.priority_at(i),
+            index.copies(i),
+            index.rucksacks(i),
+            item_total
+        );
+        copies += index.copies(i);
+        total += item_total;
+    }
+    println!("{} copies, priority total {}", copies, total);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let table = PriorityTable::puzzle();
+        let index = ItemIndex::build(include_str!("../input-test.txt"), &table).unwrap();
+
+        let p = table.index('p').unwrap();
+        assert_eq!(
+            index.occurrences(p),
+            &[
+                Occurrence {
+                    line: 0,
+                    left: 1,
+                    right: 1
+                },
+                Occurrence {
+                    line: 5,
+                    left: 0,
+                    right: 1
+                },
+            ]
+        );
+        assert_eq!((index.copies(p), index.rucksacks(p)), (3, 2));
+
+        let z = table.index('Z').unwrap();
+        assert_eq!((index.copies(z), index.rucksacks(z)), (7, 4));
+        let y = table.index('y').unwrap();
+        assert_eq!((index.copies(y), index.rucksacks(y)), (0, 0));
+    }
+
+    #[test]
+    fn unknown_items() {
+        let table = PriorityTable::puzzle();
+        assert_eq!(
+            ItemIndex::build("abab\nab1b\n", &table).err(),
+            Some("line 2: '1' is not an item type".to_string())
+        );
+    }
+}
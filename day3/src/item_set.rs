@@ -0,0 +1,193 @@
+// A set of item types, one bit per entry of the priority table: bit i holds the i-th item type of
+// the table. The first 64 entries live in a single word, so with the puzzle's 52 letters an
+// intersection or a union is a single `&` or `|` and nothing is allocated; bigger tables spill the
+// other entries into extra words.
+//
+// `high` never ends with a zero word, so two sets holding the same items are equal.
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ItemSet {
+    low: u64,
+    // Entries 64 and up, 64 per word.
+    high: Vec<u64>,
+}
+
+impl ItemSet {
+    pub fn new() -> ItemSet {
+        return ItemSet::default();
+    }
+
+    // The set of the first 64 entries whose bits are set in `bits`.
+    pub fn from_word(bits: u64) -> ItemSet {
+        return ItemSet {
+            low: bits,
+            high: Vec::new(),
+        };
+    }
+
+    fn trim(mut self) -> ItemSet {
+        while self.high.last() == Some(&0) {
+            self.high.pop();
+        }
+        return self;
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        if index < 64 {
+            self.low |= 1 << index;
+            return;
+        }
+        let word = index / 64 - 1;
+        if self.high.len() <= word {
+            self.high.resize(word + 1, 0);
+        }
+        self.high[word] |= 1 << (index % 64);
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < 64 {
+            self.low &= !(1 << index);
+            return;
+        }
+        if let Some(word) = self.high.get_mut(index / 64 - 1) {
+            *word &= !(1 << (index % 64));
+        }
+        *self = std::mem::take(self).trim();
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        if index < 64 {
+            return self.low & (1 << index) != 0;
+        }
+        return self
+            .high
+            .get(index / 64 - 1)
+            .is_some_and(|x| x & (1 << (index % 64)) != 0);
+    }
+
+    pub fn intersection(&self, other: &ItemSet) -> ItemSet {
+        let high: Vec<u64> = self
+            .high
+            .iter()
+            .zip(&other.high)
+            .map(|(a, b)| a & b)
+            .collect();
+        return ItemSet {
+            low: self.low & other.low,
+            high,
+        }
+        .trim();
+    }
+
+    pub fn union(&self, other: &ItemSet) -> ItemSet {
+        let (longer, shorter) = if self.high.len() >= other.high.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut high = longer.high.clone();
+        for (a, b) in high.iter_mut().zip(&shorter.high) {
+            *a |= b;
+        }
+        return ItemSet {
+            low: self.low | other.low,
+            high,
+        };
+    }
+
+    pub fn difference(&self, other: &ItemSet) -> ItemSet {
+        let mut high = self.high.clone();
+        for (a, b) in high.iter_mut().zip(&other.high) {
+            *a &= !b;
+        }
+        return ItemSet {
+            low: self.low & !other.low,
+            high,
+        }
+        .trim();
+    }
+
+    pub fn len(&self) -> usize {
+        let high: u32 = self.high.iter().map(|x| x.count_ones()).sum();
+        return (self.low.count_ones() + high) as usize;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.low == 0 && self.high.is_empty();
+    }
+
+    // The table indices of the items, in table order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        return std::iter::once(self.low)
+            .chain(self.high.iter().copied())
+            .enumerate()
+            .flat_map(|(word, bits)| {
+                let mut bits = bits;
+                std::iter::from_fn(move || {
+                    if bits == 0 {
+                        return None;
+                    }
+                    let lowest = bits.trailing_zeros() as usize;
+                    bits &= bits - 1;
+                    Some(word * 64 + lowest)
+                })
+            });
+    }
+
+    // The first item in table order.
+    pub fn first(&self) -> Option<usize> {
+        return self.iter().next();
+    }
+}
+
+impl FromIterator<usize> for ItemSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(indices: I) -> ItemSet {
+        let mut set = ItemSet::new();
+        for index in indices {
+            set.insert(index);
+        }
+        return set;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn past_the_first_word() {
+        let mut set = ItemSet::new();
+        for index in [3, 63, 64, 100, 200] {
+            set.insert(index);
+        }
+        assert_eq!(set.len(), 5);
+        assert!(set.contains(64) && set.contains(100) && set.contains(200));
+        assert!(!set.contains(65) && !set.contains(199) && !set.contains(1000));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![3, 63, 64, 100, 200]);
+
+        let other: ItemSet = [63, 100, 150, 200].into_iter().collect();
+        let shared = set.intersection(&other);
+        assert_eq!(shared.iter().collect::<Vec<_>>(), vec![63, 100, 200]);
+        assert_eq!(shared.first(), Some(63));
+        let union = set.union(&other);
+        assert_eq!(union.len(), 6);
+        assert!(union.contains(150));
+        let difference = set.difference(&other);
+        assert_eq!(difference.iter().collect::<Vec<_>>(), vec![3, 64]);
+    }
+
+    #[test]
+    fn equal_sets_compare_equal() {
+        // The high words left empty by an intersection or a removal must not make sets differ.
+        let a: ItemSet = [1, 300].into_iter().collect();
+        let b: ItemSet = [1, 70].into_iter().collect();
+        assert_eq!(a.intersection(&b), ItemSet::from_word(1 << 1));
+
+        let mut c = a.clone();
+        c.remove(300);
+        assert_eq!(c, ItemSet::from_word(1 << 1));
+        c.remove(1);
+        assert!(c.is_empty());
+        assert_eq!(a.difference(&a), ItemSet::new());
+    }
+}
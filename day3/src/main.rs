@@ -1,3 +1,4 @@
+#![allow(clippy::needless_return)]
 /*
 --- Day 3: Rucksack Reorganization ---
 One Elf has the important job of loading all of the rucksacks with supplies for the jungle journey. Unfortunately, that Elf didn't quite follow the packing instructions, and so a few items now need to be rearranged.
@@ -57,93 +58,268 @@ Priorities for these items must still be found to organize the sticker attachmen
 Find the item type that corresponds to the badges of each three-Elf group. What is the sum of the priorities of those item types?
 
 */
-use std::{fs::File, io::Read};
-
-fn read_input_file() -> String {
-    let mut file = File::open("input.txt").unwrap();
-    let mut fileContent = String::new();
-    file.read_to_string(&mut fileContent);
-    return fileContent;
+mod bench;
+mod discover;
+mod index;
+mod item_set;
+mod priorities;
+mod repack;
+mod validate;
+#[cfg(test)]
+mod xorshift;
+
+use std::{collections::HashMap, env, fs, str::FromStr};
+
+use item_set::ItemSet;
+use priorities::PriorityTable;
+
+// The list at `path`, the mode stops with the reason when it can't be read as text.
+fn read_input_file(path: &str) -> String {
+    return fs::read_to_string(path).unwrap_or_else(|e| {
+        println!("cannot read {}: {}", path, e);
+        std::process::exit(1);
+    });
 }
 
-static ALPHABET_VEC: [&'static char; 52] = [
-    &'a', &'b', &'c', &'d', &'e', &'f', &'g', &'h', &'i', &'j', &'k', &'l', &'m', &'n', &'o', &'p',
-    &'q', &'r', &'s', &'t', &'u', &'v', &'w', &'x', &'y', &'z', &'A', &'B', &'C', &'D', &'E', &'F',
-    &'G', &'H', &'I', &'J', &'K', &'L', &'M', &'N', &'O', &'P', &'Q', &'R', &'S', &'T', &'U', &'V',
-    &'W', &'X', &'Y', &'Z',
-];
-
-fn get_priority(&letter: &char) -> i32 {
-    let index = ALPHABET_VEC.iter().position(|&r| r == &letter).unwrap();
-    return index as i32 + 1;
+// For the modes: prints why the input can't be used and stops.
+fn exit_on_error<T>(result: Result<T, String>) -> T {
+    return result.unwrap_or_else(|message| {
+        println!("{}", message);
+        std::process::exit(1);
+    });
 }
 
-const ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz";
+// The item types of a rucksack, `line` is its index in the list, starting at 0.
+fn get_rucksack_set(rucksack: &str, line: usize, table: &PriorityTable) -> Result<ItemSet, String> {
+    return table
+        .set(rucksack)
+        .map_err(|e| format!("line {}: {}", line + 1, e));
+}
 
+// Splits on the middle item, which isn't the middle byte once items go beyond ASCII.
 fn get_rucksack_compartment(line: &str) -> (&str, &str) {
-    let splitted = line.split_at(line.len() / 2);
-    return splitted;
+    if line.is_ascii() {
+        return line.split_at(line.len() / 2);
+    }
+    let middle = line
+        .char_indices()
+        .nth(line.chars().count() / 2)
+        .map_or(line.len(), |x| x.0);
+    return line.split_at(middle);
 }
 
-fn get_shared_value2(rufpack: (&str, &str, &str)) -> Option<char> {
-    for char in rufpack.0.chars() {
-        if (rufpack.1.contains(char) && rufpack.2.contains(char)) {
-            return Some(char);
-        }
+// The item types every rucksack of the group carries, `first_line` is the index of its first one.
+fn get_badges(group: &[&str], first_line: usize, table: &PriorityTable) -> Result<ItemSet, String> {
+    let mut badges: Option<ItemSet> = None;
+    for (i, rucksack) in group.iter().enumerate() {
+        let set = get_rucksack_set(rucksack, first_line + i, table)?;
+        badges = Some(match badges {
+            Some(shared) => shared.intersection(&set),
+            None => set,
+        });
     }
-
-    return None;
+    return Ok(badges.unwrap_or_default());
 }
 
-fn get_share_values(compartments: (&str, &str)) -> Option<char> {
-    let left = compartments.0;
-    let right = compartments.1;
-    let mut shared: Vec<char> = Vec::new();
+// Consecutive lines carried by the same group of elves.
+pub struct Group<'a> {
+    // Index of the group's first line in the input, starting at 0.
+    pub first_line: usize,
+    pub rucksacks: Vec<&'a str>,
+    pub badges: ItemSet,
+}
 
-    for c in left.chars() {
-        if (right.contains(c)) {
-            return Some(c);
-            //shared.push(c);
-        }
+// The input cut in groups of `group_size` lines, and the lines left over at the end when there are
+// not enough of them for a last group.
+fn get_groups<'a>(
+    input: &'a str,
+    group_size: usize,
+    table: &PriorityTable,
+) -> Result<(Vec<Group<'a>>, Option<Group<'a>>), String> {
+    let lines: Vec<&str> = input.lines().collect();
+    let mut groups: Vec<Group> = Vec::new();
+    for (i, chunk) in lines.chunks(group_size).enumerate() {
+        groups.push(Group {
+            first_line: i * group_size,
+            rucksacks: chunk.to_vec(),
+            badges: get_badges(chunk, i * group_size, table)?,
+        });
     }
 
-    return None;
+    let incomplete = match groups.last() {
+        Some(last) if last.rucksacks.len() < group_size => groups.pop(),
+        _ => None,
+    };
+    return Ok((groups, incomplete));
+}
 
-    //    return shared;
+// The item types found in both compartments of the rucksack at index `line`.
+fn get_share_values(
+    compartments: (&str, &str),
+    line: usize,
+    table: &PriorityTable,
+) -> Result<ItemSet, String> {
+    let left = get_rucksack_set(compartments.0, line, table)?;
+    let right = get_rucksack_set(compartments.1, line, table)?;
+    return Ok(left.intersection(&right));
 }
 
-fn part2(input: String) {
-    let lines = input.lines();
+// The groups without a badge add nothing, `part2` reports them.
+fn sum_part2(input: &str, group_size: usize, table: &PriorityTable) -> Result<i32, String> {
+    let (groups, _) = get_groups(input, group_size, table)?;
+    return Ok(groups
+        .iter()
+        .filter_map(|x| x.badges.first())
+        .map(|badge| table.priority_at(badge))
+        .sum());
+}
 
-    let mut line_iter = lines.into_iter().peekable();
+fn sum_part1(input: &str, table: &PriorityTable) -> Result<i32, String> {
+    let mut result = 0;
+    for (i, line) in input.lines().enumerate() {
+        let compartments = get_rucksack_compartment(line);
+        let shared = get_share_values(compartments, i, table)?;
+        result += table.priority_at(shared.first().unwrap());
+    }
+    return Ok(result);
+}
 
-    let mut all_shared:Vec<char> = Vec::new();
-    while line_iter.peek().is_some() {
-        let chunk: Vec<_> = line_iter.by_ref().take(3).collect();
-        let shared = get_shared_value2((chunk[0], chunk[1], chunk[2]));
-        all_shared.push(shared.unwrap());
-        println!("{:?}", chunk);
+fn part2(input: String, options: &Options) {
+    let (groups, incomplete) =
+        exit_on_error(get_groups(&input, options.group_size, &options.table));
+    for group in groups.iter().filter(|x| x.badges.is_empty()) {
+        println!(
+            "lines {} to {} share no item type, no badge, skipped",
+            group.first_line + 1,
+            group.first_line + group.rucksacks.len()
+        );
     }
-    let result: i32 = all_shared.iter().map(|x| get_priority(x)).sum();
+    if let Some(group) = incomplete {
+        println!(
+            "lines {} to {} are only {} rucksacks, not a group of {}, skipped",
+            group.first_line + 1,
+            group.first_line + group.rucksacks.len(),
+            group.rucksacks.len(),
+            options.group_size
+        );
+    }
+    println!(
+        "{}",
+        exit_on_error(sum_part2(&input, options.group_size, &options.table))
+    );
+}
 
-    println!("{}", result);
+// The options the modes share (the input path, `--group-size` and `--priorities`) and the flags of
+// the mode itself.
+pub struct Options {
+    pub path: String,
+    pub group_size: usize,
+    pub table: PriorityTable,
+    flags: HashMap<String, String>,
 }
 
-fn part1(input: String) {
-    let lines = input.lines();
-    let mut all_shared: Vec<char> = Vec::new();
+impl Options {
+    fn parse(args: &[String]) -> Options {
+        let mut path = "input.txt";
+        let mut group_size = 3;
+        let mut table = PriorityTable::puzzle();
+        let mut flags: HashMap<String, String> = HashMap::new();
+
+        let mut args_iter = args.iter();
+        while let Some(arg) = args_iter.next() {
+            match arg.as_str() {
+                "--group-size" => {
+                    group_size = args_iter
+                        .next()
+                        .and_then(|x| x.parse().ok())
+                        .filter(|x| *x > 0)
+                        .expect("--group-size needs a positive number")
+                }
+                "--priorities" => {
+                    let table_path = args_iter.next().expect("--priorities needs a file");
+                    table = PriorityTable::load(table_path)
+                        .unwrap_or_else(|e| panic!("invalid priorities: {}", e));
+                }
+                flag if flag.starts_with("--") => {
+                    let value = args_iter
+                        .next()
+                        .unwrap_or_else(|| panic!("{} needs a value", flag));
+                    flags.insert(flag.to_string(), value.clone());
+                }
+                other => path = other,
+            }
+        }
 
-    for line in lines {
-        let compartments = get_rucksack_compartment(&line);
-        let shared = get_share_values(compartments);
-        all_shared.push(shared.unwrap());
+        return Options {
+            path: path.to_string(),
+            group_size,
+            table,
+            flags,
+        };
     }
 
-    let result: i32 = all_shared.iter().map(|x| get_priority(x)).sum();
-    println!("{}", result);
+    pub fn flag(&self, name: &str) -> Option<&str> {
+        return self.flags.get(name).map(|x| x.as_str());
+    }
+
+    pub fn number<T: FromStr>(&self, name: &str) -> Option<T> {
+        return self.flag(name).map(|x| {
+            x.parse::<T>()
+                .unwrap_or_else(|_| panic!("{} must be a number", name))
+        });
+    }
 }
 
+// day3 [bench|discover|index|repack|validate] [--group-size k] [--priorities <file>] [mode flags] [input]
 fn main() {
-    let input = read_input_file();
-    part2(input);
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(|x| x.as_str()) {
+        Some("bench") => bench::run(&args[2..]),
+        Some("discover") => discover::run(&Options::parse(&args[2..])),
+        Some("index") => index::run(&Options::parse(&args[2..])),
+        Some("repack") => repack::run(&Options::parse(&args[2..])),
+        Some("validate") => validate::run(&Options::parse(&args[2..])),
+        _ => {
+            let options = Options::parse(&args[1..]);
+            part2(read_input_file(&options.path), &options);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../input-test.txt");
+
+    #[test]
+    fn example() {
+        let table = PriorityTable::puzzle();
+        assert_eq!(sum_part1(EXAMPLE, &table), Ok(157));
+        assert_eq!(sum_part2(EXAMPLE, 3, &table), Ok(70));
+    }
+
+    #[test]
+    fn groups_without_a_badge_are_skipped() {
+        let table = PriorityTable::puzzle();
+        let (groups, incomplete) = get_groups(EXAMPLE, 4, &table).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert!(groups[0].badges.is_empty());
+        assert_eq!(incomplete.map(|x| x.rucksacks.len()), Some(2));
+        assert_eq!(sum_part2(EXAMPLE, 4, &table), Ok(0));
+    }
+
+    #[test]
+    fn unknown_items_are_reported() {
+        let table = PriorityTable::puzzle();
+        let input = "vJrwpWtwJgWrhcsFMMfFFhFp\nabcA1b\n";
+        assert_eq!(
+            sum_part1(input, &table),
+            Err("line 2: '1' is not an item type".to_string())
+        );
+        assert_eq!(
+            sum_part2(input, 2, &table),
+            Err("line 2: '1' is not an item type".to_string())
+        );
+    }
 }
@@ -0,0 +1,238 @@
+// The item types a rucksack can hold and their priorities. A table is written one entry per line,
+// either an item and its priority or a range of items and the priority of the first one:
+//
+// a..z 1
+// A..Z 27
+// ? 60
+//
+// which is the puzzle's table with `?` added. An item is any single character, digits,
+// punctuation or beyond ASCII alike. The order of the entries is the order of the `ItemSet` bits,
+// and of the items in the reports.
+
+use std::collections::HashMap;
+
+use crate::item_set::ItemSet;
+
+const PUZZLE: &str = "\
+a..z 1
+A..Z 27
+";
+
+// An ASCII character that isn't in the table.
+const NOT_AN_ITEM: u32 = u32::MAX;
+
+#[derive(Debug, Clone)]
+pub struct PriorityTable {
+    items: Vec<char>,
+    priorities: Vec<i32>,
+    // Index in the table of each ASCII character, NOT_AN_ITEM when it isn't an item type.
+    ascii: [u32; 128],
+    others: HashMap<char, usize>,
+}
+
+impl PriorityTable {
+    pub fn puzzle() -> PriorityTable {
+        return PriorityTable::parse(PUZZLE).unwrap();
+    }
+
+    pub fn load(path: &str) -> Result<PriorityTable, String> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+        return PriorityTable::parse(&content);
+    }
+
+    pub fn parse(s: &str) -> Result<PriorityTable, String> {
+        let mut table = PriorityTable {
+            items: Vec::new(),
+            priorities: Vec::new(),
+            ascii: [NOT_AN_ITEM; 128],
+            others: HashMap::new(),
+        };
+
+        for (i, line) in s.lines().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.is_empty() || words[0].starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| format!("line {}: {}", i + 1, message);
+            if words.len() != 2 {
+                return Err(error("expected an item or a range, and a priority"));
+            }
+            let priority = words[1]
+                .parse::<i32>()
+                .map_err(|_| error("the priority must be a number"))?;
+
+            let chars: Vec<char> = words[0].chars().collect();
+            let (first, last) = match chars[..] {
+                [item] => (item, item),
+                [first, '.', '.', last] if first <= last => (first, last),
+                _ => return Err(error("expected a single character or a range like a..z")),
+            };
+            for (offset, item) in (first..=last).enumerate() {
+                table
+                    .add(item, priority + offset as i32)
+                    .map_err(|e| error(&e))?;
+            }
+        }
+
+        if table.items.is_empty() {
+            return Err("the table has no items".to_string());
+        }
+        return Ok(table);
+    }
+
+    fn add(&mut self, item: char, priority: i32) -> Result<(), String> {
+        if self.index(item).is_some() {
+            return Err(format!("{:?} is listed twice", item));
+        }
+        let index = self.items.len();
+        if item.is_ascii() {
+            self.ascii[item as usize] = index as u32;
+        } else {
+            self.others.insert(item, index);
+        }
+        self.items.push(item);
+        self.priorities.push(priority);
+        return Ok(());
+    }
+
+    pub fn len(&self) -> usize {
+        return self.items.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.items.is_empty();
+    }
+
+    // Position of an item type in the table, None when the character isn't one.
+    pub fn index(&self, item: char) -> Option<usize> {
+        if item.is_ascii() {
+            return match self.ascii[item as usize] {
+                NOT_AN_ITEM => None,
+                index => Some(index as usize),
+            };
+        }
+        return self.others.get(&item).copied();
+    }
+
+    pub fn item(&self, index: usize) -> char {
+        return self.items[index];
+    }
+
+    pub fn priority(&self, item: char) -> Option<i32> {
+        return self.index(item).map(|x| self.priorities[x]);
+    }
+
+    pub fn priority_at(&self, index: usize) -> i32 {
+        return self.priorities[index];
+    }
+
+    // The item types of `items`, or the first character that isn't one.
+    pub fn set(&self, items: &str) -> Result<ItemSet, String> {
+        let not_an_item = |c: char| format!("{:?} is not an item type", c);
+        if items.is_ascii() && self.len() <= 64 {
+            // Every index fits in one word, no need to go through `insert`.
+            let mut bits: u64 = 0;
+            for b in items.bytes() {
+                match self.ascii[b as usize] {
+                    NOT_AN_ITEM => return Err(not_an_item(b as char)),
+                    index => bits |= 1 << index,
+                }
+            }
+            return Ok(ItemSet::from_word(bits));
+        }
+        let mut set = ItemSet::new();
+        if items.is_ascii() {
+            for b in items.bytes() {
+                match self.ascii[b as usize] {
+                    NOT_AN_ITEM => return Err(not_an_item(b as char)),
+                    index => set.insert(index as usize),
+                }
+            }
+            return Ok(set);
+        }
+        for c in items.chars() {
+            let index = self.index(c).ok_or_else(|| not_an_item(c))?;
+            set.insert(index);
+        }
+        return Ok(set);
+    }
+
+    // The item types of `items`, leaving out the characters that aren't.
+    pub fn known_set(&self, items: impl Iterator<Item = char>) -> ItemSet {
+        return items.filter_map(|x| self.index(x)).collect();
+    }
+
+    // The items of `set`, in table order.
+    pub fn items_of(&self, set: &ItemSet) -> String {
+        return set.iter().map(|x| self.items[x]).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../input-test.txt");
+
+    #[test]
+    fn puzzle_priorities() {
+        let table = PriorityTable::puzzle();
+        assert_eq!(table.len(), 52);
+        assert_eq!(table.priority('a'), Some(1));
+        assert_eq!(table.priority('z'), Some(26));
+        assert_eq!(table.priority('A'), Some(27));
+        assert_eq!(table.priority('Z'), Some(52));
+        assert_eq!(table.priority('?'), None);
+    }
+
+    #[test]
+    fn custom_table_changes_the_sums() {
+        let table = PriorityTable::parse("A..Z 1\na..z 27\n").unwrap();
+        assert_eq!(table.priority('a'), Some(27));
+        assert_eq!(table.priority('A'), Some(1));
+        // p L P v t s, the shared items of the example, are worth 16 38 42 22 20 19 in the puzzle.
+        assert_eq!(
+            crate::sum_part1(EXAMPLE, &table).unwrap(),
+            42 + 12 + 16 + 48 + 46 + 45
+        );
+        // r and Z, the badges.
+        assert_eq!(crate::sum_part2(EXAMPLE, 3, &table), Ok(44 + 26));
+    }
+
+    #[test]
+    fn tables_past_64_items() {
+        let table =
+            PriorityTable::parse("a..z 1\nA..Z 27\n0..9 53\n!../ 63\né 100\n€ 200\n").unwrap();
+        assert_eq!(table.len(), 79);
+        assert_eq!(table.index('é'), Some(77));
+        assert_eq!(table.priority('€'), Some(200));
+
+        let set = table.set("a/é€é").unwrap();
+        assert_eq!(set.len(), 4);
+        assert!(set.contains(table.index('/').unwrap()));
+        assert_eq!(table.items_of(&set), "a/é€");
+        assert_eq!(
+            table.items_of(
+                &table
+                    .set("9/")
+                    .unwrap()
+                    .intersection(&table.set("/Q").unwrap())
+            ),
+            "/"
+        );
+
+        // é is in both halves of the first rucksack, € in both of the second.
+        assert_eq!(crate::sum_part1("xéyé\n€x€y\n", &table), Ok(100 + 200));
+        assert_eq!(crate::sum_part2("a€\nb€\n€9\n", 3, &table), Ok(200));
+    }
+
+    #[test]
+    fn invalid_tables() {
+        assert!(PriorityTable::parse("").is_err());
+        assert!(PriorityTable::parse("a 1\na 2\n").is_err());
+        assert!(PriorityTable::parse("z..a 1\n").is_err());
+        assert!(PriorityTable::parse("ab 1\n").is_err());
+        assert!(PriorityTable::parse("a one\n").is_err());
+    }
+}
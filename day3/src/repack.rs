@@ -0,0 +1,290 @@
+// Plans the fewest item moves between the two compartments of a rucksack so that no item type is in
+// both of them, with the compartments keeping their size.
+//
+// A plan decides how many items of each type end up on the left: all of them, none, or some, in
+// which case the type stays in both compartments. The left counts must add up to the left
+// compartment's size, and the moves are how far each count is from the current one. That's a
+// knapsack over the item types, solved for every left size at once, keeping for each the fewest
+// types left in both compartments and then the fewest moves.
+//
+// When no choice of whole types fills the left compartment exactly, the plan leaves as few types
+// as possible in both compartments (never more than one: whole types fill the left until the next
+// one doesn't fit, and that one is split), and reports them.
+
+use crate::priorities::PriorityTable;
+use crate::{get_rucksack_compartment, Options};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Move {
+    pub item: char,
+    pub count: usize,
+    // From the right compartment to the left one, otherwise the other way.
+    pub to_left: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    pub moves: Vec<Move>,
+    // The item types still in both compartments after the moves, in table order, empty when the
+    // rucksack could be repacked as asked.
+    pub shared: Vec<char>,
+}
+
+impl Plan {
+    pub fn move_count(&self) -> usize {
+        return self.moves.iter().map(|x| x.count).sum();
+    }
+}
+
+// Fails on the first character that isn't an item type.
+pub fn plan(line: &str, table: &PriorityTable) -> Result<Plan, String> {
+    let (left, right) = get_rucksack_compartment(line);
+    let index = |c: char| {
+        table
+            .index(c)
+            .ok_or_else(|| format!("{:?} is not an item type", c))
+    };
+
+    // (left count, right count) of each item type, in table order.
+    let mut counts: Vec<(usize, usize)> = vec![(0, 0); table.len()];
+    for c in left.chars() {
+        counts[index(c)?].0 += 1;
+    }
+    for c in right.chars() {
+        counts[index(c)?].1 += 1;
+    }
+    let types: Vec<usize> = (0..table.len()).filter(|x| counts[*x] != (0, 0)).collect();
+    // An odd rucksack can't have equal compartments, it keeps the sizes it has.
+    let left_size = left.chars().count();
+
+    // best[k][s]: (types in both compartments, moves) placing the first k types with s items on
+    // the left, and kept[k][s] how many of the k-th type's items stay on the left to get there.
+    let mut best: Vec<Vec<Option<(usize, usize)>>> =
+        vec![vec![None; left_size + 1]; types.len() + 1];
+    let mut kept: Vec<Vec<usize>> = vec![vec![0; left_size + 1]; types.len() + 1];
+    best[0][0] = Some((0, 0));
+    for (k, &t) in types.iter().enumerate() {
+        let (l, r) = counts[t];
+        for s in 0..=left_size {
+            let Some((shared, moves)) = best[k][s] else {
+                continue;
+            };
+            for x in 0..=(l + r).min(left_size - s) {
+                let split = (x > 0 && x < l + r) as usize;
+                let candidate = (shared + split, moves + x.abs_diff(l));
+                if best[k + 1][s + x].is_none_or(|current| candidate < current) {
+                    best[k + 1][s + x] = Some(candidate);
+                    kept[k + 1][s + x] = x;
+                }
+            }
+        }
+    }
+
+    let mut moves: Vec<Move> = Vec::new();
+    let mut shared: Vec<char> = Vec::new();
+    let mut s = left_size;
+    for k in (0..types.len()).rev() {
+        let t = types[k];
+        let (l, r) = counts[t];
+        let x = kept[k + 1][s];
+        s -= x;
+        if x > 0 && x < l + r {
+            shared.push(table.item(t));
+        }
+        if x != l {
+            moves.push(Move {
+                item: table.item(t),
+                count: x.abs_diff(l),
+                to_left: x > l,
+            });
+        }
+    }
+    moves.reverse();
+    shared.reverse();
+
+    return Ok(Plan { moves, shared });
+}
+
+fn describe(moves: &[&Move]) -> String {
+    let parts: Vec<String> = moves
+        .iter()
+        .map(|x| format!("{} x{}", x.item, x.count))
+        .collect();
+    return parts.join(", ");
+}
+
+// day3 repack [--priorities <file>] [input]
+pub fn run(options: &Options) {
+    let input = crate::read_input_file(&options.path);
+
+    let mut total_moves = 0;
+    let mut repacked = 0;
+    let mut unseparated = 0;
+    for (i, line) in input.lines().enumerate() {
+        let plan = crate::exit_on_error(
+            plan(line, &options.table).map_err(|e| format!("line {}: {}", i + 1, e)),
+        );
+        if plan.moves.is_empty() && plan.shared.is_empty() {
+            continue;
+        }
+
+        let mut parts: Vec<String> = Vec::new();
+        let to_left: Vec<&Move> = plan.moves.iter().filter(|x| x.to_left).collect();
+        let to_right: Vec<&Move> = plan.moves.iter().filter(|x| !x.to_left).collect();
+        if !to_left.is_empty() {
+            parts.push(format!("right to left {}", describe(&to_left)));
+        }
+        if !to_right.is_empty() {
+            parts.push(format!("left to right {}", describe(&to_right)));
+        }
+        if !plan.shared.is_empty() {
+            let shared: String = plan.shared.iter().collect();
+            parts.push(format!(
+                "cannot separate every type, {} stays in both compartments",
+                shared
+            ));
+            unseparated += 1;
+        }
+        println!(
+            "line {}: {} moves: {}",
+            i + 1,
+            plan.move_count(),
+            parts.join("; ")
+        );
+
+        total_moves += plan.move_count();
+        repacked += (plan.move_count() > 0) as usize;
+    }
+
+    println!(
+        "{} moves in {} rucksacks, {} rucksacks keep an item type in both compartments",
+        total_moves, repacked, unseparated
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xorshift::XorShift;
+
+    // Applies the plan and returns the compartments' item counts, by table index.
+    fn apply(line: &str, plan: &Plan, table: &PriorityTable) -> Vec<(usize, usize)> {
+        let (left, right) = get_rucksack_compartment(line);
+        let mut counts = vec![(0, 0); table.len()];
+        for c in left.chars() {
+            counts[table.index(c).unwrap()].0 += 1;
+        }
+        for c in right.chars() {
+            counts[table.index(c).unwrap()].1 += 1;
+        }
+        for x in &plan.moves {
+            let count = &mut counts[table.index(x.item).unwrap()];
+            if x.to_left {
+                count.1 -= x.count;
+                count.0 += x.count;
+            } else {
+                count.0 -= x.count;
+                count.1 += x.count;
+            }
+        }
+        return counts;
+    }
+
+    // (types in both compartments, moves) of the best way to choose how many of each type stay
+    // left, trying every choice.
+    fn brute_force(counts: &[(usize, usize)], left_size: usize) -> (usize, usize) {
+        let Some((&(l, r), rest)) = counts.split_first() else {
+            return if left_size == 0 {
+                (0, 0)
+            } else {
+                (usize::MAX, 0)
+            };
+        };
+        let mut best = (usize::MAX, 0);
+        for x in 0..=(l + r).min(left_size) {
+            let (shared, moves) = brute_force(rest, left_size - x);
+            if shared == usize::MAX {
+                continue;
+            }
+            let split = (x > 0 && x < l + r) as usize;
+            best = best.min((shared + split, moves + x.abs_diff(l)));
+        }
+        return best;
+    }
+
+    #[test]
+    fn example_separates() {
+        let table = PriorityTable::puzzle();
+        let plan = plan("vJrwpWtwJgWrhcsFMMfFFhFp", &table).unwrap();
+        assert!(plan.shared.is_empty());
+        assert_eq!(plan.move_count(), 2);
+    }
+
+    #[test]
+    fn reports_types_left_in_both() {
+        let table = PriorityTable::puzzle();
+        // A single type can't be anywhere but in both compartments.
+        let plan_one = plan("aaaa", &table).unwrap();
+        assert_eq!(plan_one.shared, vec!['a']);
+        assert_eq!(plan_one.move_count(), 0);
+
+        // 3 a and 1 b can't fill a compartment of 2 with whole types: only one needs splitting,
+        // and leaving a split as it is costs nothing.
+        let plan_two = plan("aaab", &table).unwrap();
+        assert_eq!(plan_two.shared, vec!['a']);
+        assert_eq!(plan_two.move_count(), 0);
+
+        assert_eq!(
+            plan("ab1b", &table),
+            Err("'1' is not an item type".to_string())
+        );
+
+        // The compartments keep their size in both cases.
+        for (line, plan) in [("aaaa", &plan_one), ("aaab", &plan_two)] {
+            let left: usize = apply(line, plan, &table).iter().map(|x| x.0).sum();
+            assert_eq!(left, 2);
+        }
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let table = PriorityTable::puzzle();
+        let mut rng = XorShift::new(0x2545f4914f6cdd1d);
+        let mut next = |bound: u64| rng.below(bound) as usize;
+
+        for _ in 0..500 {
+            let length = 2 * (1 + next(6)) + next(2);
+            let types = 1 + next(4);
+            let line: String = (0..length)
+                .map(|_| (b'a' + next(types as u64) as u8) as char)
+                .collect();
+            let plan = plan(&line, &table).unwrap();
+
+            let (left, right) = get_rucksack_compartment(&line);
+            let mut counts = vec![(0, 0); table.len()];
+            for c in left.chars() {
+                counts[table.index(c).unwrap()].0 += 1;
+            }
+            for c in right.chars() {
+                counts[table.index(c).unwrap()].1 += 1;
+            }
+            let present: Vec<(usize, usize)> =
+                counts.into_iter().filter(|x| *x != (0, 0)).collect();
+            let expected = brute_force(&present, left.len());
+            assert_eq!((plan.shared.len(), plan.move_count()), expected, "{}", line);
+
+            let after = apply(&line, &plan, &table);
+            assert_eq!(
+                after.iter().map(|x| x.0).sum::<usize>(),
+                left.len(),
+                "{}",
+                line
+            );
+            let shared: Vec<char> = (0..table.len())
+                .filter(|x| after[*x].0 > 0 && after[*x].1 > 0)
+                .map(|x| table.item(x))
+                .collect();
+            assert_eq!(plan.shared, shared, "{}", line);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+// Checks the assumptions both parts make about the list: every rucksack splits into two equal
+// compartments sharing exactly one item type, every group shares exactly one badge, and every
+// character is an item type of the priority table.
+//
+// Characters that aren't item types are reported and left out of the other checks, so one typo
+// doesn't hide the rest of the report.
+
+use std::fmt;
+
+use crate::priorities::PriorityTable;
+use crate::Options;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    OddLength(usize),
+    UnknownItems(Vec<char>),
+    NoSharedItem,
+    // The item types, in table order.
+    SharedItems(String),
+    NoBadge,
+    Badges(String),
+    // The last lines, too few for a whole group.
+    IncompleteGroup(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    // First line concerned, starting at 1.
+    pub line: usize,
+    // Last line concerned, the same as `line` for a single rucksack.
+    pub last_line: usize,
+    pub problem: Problem,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Problem::OddLength(length) => write!(f, "odd number of items ({})", length),
+            Problem::UnknownItems(chars) => {
+                write!(f, "not item types: {:?}", chars.iter().collect::<String>())
+            }
+            Problem::NoSharedItem => write!(f, "compartments share no item type"),
+            Problem::SharedItems(items) => write!(
+                f,
+                "compartments share {} item types: {}",
+                items.chars().count(),
+                items
+            ),
+            Problem::NoBadge => write!(f, "group has no badge"),
+            Problem::Badges(items) => write!(
+                f,
+                "group has {} badge candidates: {}",
+                items.chars().count(),
+                items
+            ),
+            Problem::IncompleteGroup(rucksacks) => {
+                write!(f, "only {} rucksacks left for the last group", rucksacks)
+            }
+        };
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == self.last_line {
+            return write!(f, "line {}: {}", self.line, self.problem);
+        }
+        return write!(
+            f,
+            "lines {}-{}: {}",
+            self.line, self.last_line, self.problem
+        );
+    }
+}
+
+fn check_rucksack(line: &str, table: &PriorityTable) -> Vec<Problem> {
+    let mut problems: Vec<Problem> = Vec::new();
+    let chars: Vec<char> = line.chars().collect();
+
+    let mut unknown: Vec<char> = Vec::new();
+    for &c in &chars {
+        if table.index(c).is_none() && !unknown.contains(&c) {
+            unknown.push(c);
+        }
+    }
+    if !unknown.is_empty() {
+        problems.push(Problem::UnknownItems(unknown));
+    }
+
+    if chars.len() % 2 == 1 {
+        problems.push(Problem::OddLength(chars.len()));
+        return problems;
+    }
+    let (left, right) = chars.split_at(chars.len() / 2);
+    let shared = table
+        .known_set(left.iter().cloned())
+        .intersection(&table.known_set(right.iter().cloned()));
+    match shared.len() {
+        0 => problems.push(Problem::NoSharedItem),
+        1 => {}
+        _ => problems.push(Problem::SharedItems(table.items_of(&shared))),
+    }
+    return problems;
+}
+
+pub fn validate(input: &str, group_size: usize, table: &PriorityTable) -> Vec<Finding> {
+    let lines: Vec<&str> = input.lines().collect();
+    let mut findings: Vec<Finding> = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        for problem in check_rucksack(line, table) {
+            findings.push(Finding {
+                line: i + 1,
+                last_line: i + 1,
+                problem,
+            });
+        }
+    }
+
+    for (i, group) in lines.chunks(group_size).enumerate() {
+        let line = i * group_size + 1;
+        let last_line = line + group.len() - 1;
+        let problem = if group.len() < group_size {
+            Problem::IncompleteGroup(group.len())
+        } else {
+            let badges = group
+                .iter()
+                .map(|x| table.known_set(x.chars()))
+                .reduce(|a, b| a.intersection(&b))
+                .unwrap();
+            match badges.len() {
+                0 => Problem::NoBadge,
+                1 => continue,
+                _ => Problem::Badges(table.items_of(&badges)),
+            }
+        };
+        findings.push(Finding {
+            line,
+            last_line,
+            problem,
+        });
+    }
+
+    findings.sort_by_key(|x| x.line);
+    return findings;
+}
+
+// day3 validate [--group-size k] [--priorities <file>] [input]
+//
+// Exits with 1 when something was found.
+pub fn run(options: &Options) {
+    let input = crate::read_input_file(&options.path);
+
+    let findings = validate(&input, options.group_size, &options.table);
+    for finding in &findings {
+        println!("{}", finding);
+    }
+    println!(
+        "{} rucksacks, {} problems",
+        input.lines().count(),
+        findings.len()
+    );
+    if !findings.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(line: usize, last_line: usize, problem: Problem) -> Finding {
+        return Finding {
+            line,
+            last_line,
+            problem,
+        };
+    }
+
+    #[test]
+    fn example_is_clean() {
+        let table = PriorityTable::puzzle();
+        assert_eq!(
+            validate(include_str!("../input-test.txt"), 3, &table),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn every_problem() {
+        let table = PriorityTable::puzzle();
+        // Lines 1 to 3 share nothing, lines 4 to 6 share x and y, line 7 is alone.
+        let input = "abca\nabcXYZ\ndefd1\nxyxy\nxzyz\nxAyA\nmm\n";
+        let findings = validate(input, 3, &table);
+        assert_eq!(
+            findings,
+            vec![
+                finding(1, 3, Problem::NoBadge),
+                finding(2, 2, Problem::NoSharedItem),
+                finding(3, 3, Problem::UnknownItems(vec!['1'])),
+                finding(3, 3, Problem::OddLength(5)),
+                finding(4, 4, Problem::SharedItems("xy".to_string())),
+                finding(4, 6, Problem::Badges("xy".to_string())),
+                finding(7, 7, Problem::IncompleteGroup(1)),
+            ]
+        );
+
+        let report: Vec<String> = findings.iter().map(|x| x.to_string()).collect();
+        assert_eq!(report[0], "lines 1-3: group has no badge");
+        assert_eq!(report[2], "line 3: not item types: \"1\"");
+        assert_eq!(report[4], "line 4: compartments share 2 item types: xy");
+        assert_eq!(
+            report[6],
+            "line 7: only 1 rucksacks left for the last group"
+        );
+    }
+
+    #[test]
+    fn unknown_items_are_left_out() {
+        let table = PriorityTable::puzzle();
+        // Each unknown character is reported once, the rucksack still shares only a.
+        let findings = validate("a1!a1!\n", 1, &table);
+        assert_eq!(
+            findings,
+            vec![finding(1, 1, Problem::UnknownItems(vec!['1', '!']))]
+        );
+    }
+}
@@ -0,0 +1,24 @@
+// xorshift64, for the tests: varied inputs from a seed without a dependency.
+
+pub struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    // The seed must not be 0, xorshift would stay there.
+    pub fn new(seed: u64) -> XorShift {
+        return XorShift { state: seed };
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        return self.state;
+    }
+
+    // Uniform enough in 0..n, n must not be 0.
+    pub fn below(&mut self, n: u64) -> u64 {
+        return self.next_u64() % n;
+    }
+}